@@ -0,0 +1,117 @@
+// Times candidate discovery, limited solves and the full solution search on a generated 6x8 board.
+// Run `cargo bench` and `cargo bench --features parallel` to compare the sequential and parallel paths.
+
+use std::time::{Duration, Instant};
+
+use strands_solver::strands::Strands;
+use strands_solver::trie::Trie;
+
+const ROWS: usize = 6;
+const COLS: usize = 8;
+const DECOYS: usize = 400;
+const RUNS: u32 = 3;
+const EXACT_ANSWERS: usize = 7;
+const LETTERS: &[u8] = b"EEEEEAAAAIIIOOOUTTTNNNSSSRRRLLDDHHCMPGBYFWKV";
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, 0), (-1, 1), (0, 1), (1, 1),
+    (1, 0), (1, -1), (0, -1), (-1, -1)
+];
+
+// xorshift, so every run builds the same board and dictionary
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+fn word_at(board: &[Vec<char>], path: &[(usize, usize)]) -> String {
+    path.iter().map(|&(row, col)| board[row][col]).collect()
+}
+
+fn build(rng: &mut Rng, decoys: usize) -> (String, Trie) {
+    let board: Vec<Vec<char>> = (0..ROWS)
+        .map(|_| (0..COLS).map(|_| LETTERS[rng.below(LETTERS.len())] as char).collect())
+        .collect();
+    let mut trie = Trie::new();
+
+    // answers: snake through the board row by row, cut into words of 4-8 letters
+    let snake: Vec<(usize, usize)> = (0..ROWS)
+        .flat_map(|row| (0..COLS).map(move |col| (row, if row % 2 == 0 { col } else { COLS - 1 - col })))
+        .collect();
+    let mut start = 0;
+    while start < snake.len() {
+        let mut end = (start + 4 + rng.below(5)).min(snake.len());
+        if snake.len() - end < 4 {
+            end = snake.len();
+        }
+        trie.insert(word_at(&board, &snake[start..end]));
+        start = end;
+    }
+
+    // decoys: random walks, so they can all be traced on the board and compete with the answers
+    for _ in 0..decoys {
+        let len = 4 + rng.below(4);
+        let mut path = vec![(rng.below(ROWS), rng.below(COLS))];
+        while path.len() < len {
+            let (row, col) = *path.last().unwrap();
+            let next: Vec<(usize, usize)> = DIRECTIONS
+                .iter()
+                .map(|&(dir_row, dir_col)| (row as isize + dir_row, col as isize + dir_col))
+                .filter(|&(r, c)| r >= 0 && c >= 0 && r < ROWS as isize && c < COLS as isize)
+                .map(|(r, c)| (r as usize, c as usize))
+                .filter(|cell| !path.contains(cell))
+                .collect();
+            if next.is_empty() {
+                break;
+            }
+            path.push(next[rng.below(next.len())]);
+        }
+        if path.len() >= 4 {
+            trie.insert(word_at(&board, &path));
+        }
+    }
+
+    let puzzle = board
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (puzzle, trie)
+}
+
+fn time<T>(label: &str, mut run: impl FnMut() -> T) -> T {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        result = Some(run());
+        best = best.min(start.elapsed());
+    }
+    println!("{:<24} {:>10.2?} (best of {})", label, best, RUNS);
+    result.unwrap()
+}
+
+fn main() {
+    let mode = if cfg!(feature = "parallel") { "parallel" } else { "sequential" };
+    println!("strands solve benchmark ({})", mode);
+    let mut rng = Rng(0x5eed_1234_abcd_ef01);
+    let (puzzle, trie) = build(&mut rng, DECOYS);
+    let strands = Strands::new(puzzle.clone(), None);
+
+    let candidates = time("find_candidates", || strands.find_candidates(&trie));
+    println!("{:<24} {:>10}", "candidates", candidates.len());
+    let first = time("count_solutions (1)", || strands.count_solutions(&trie, 1));
+    println!("{:<24} {:>10}", "solutions", first);
+    // With the answer count fixed, most tilings have the wrong number of words. The first one that fits is in
+    // one of the last top level branches, so a limited solve gains from searching the branches side by side.
+    let exact = Strands::new(puzzle, EXACT_ANSWERS);
+    let first = time("count (1, 7 answers)", || exact.count_solutions(&trie, 1));
+    println!("{:<24} {:>10}", "solutions", first);
+    let count = time("count_solutions (all)", || strands.count_solutions(&trie, usize::MAX));
+    println!("{:<24} {:>10}", "solutions", count);
+}
@@ -0,0 +1,117 @@
+// Which cells count as next to each other, so the same solver can run Strands-like variants.
+// Coordinates are (row, col). Every model only returns cells inside the grid.
+
+use crate::model::{Coord, Grid};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Adjacency {
+    Four, // up, down, left, right
+    #[default]
+    Eight, // the four above plus diagonals, as in Strands
+    Hex, // hexagonal cells in "odd-r" layout: odd rows are shifted half a cell to the right
+    Torus, // eight neighbours, wrapping around the edges of the grid
+}
+
+const FOUR: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+const EIGHT: [(isize, isize); 8] = [
+    (-1, 0), (-1, 1), (0, 1), (1, 1),
+    (1, 0), (1, -1), (0, -1), (-1, -1)
+];
+const HEX_EVEN_ROW: [(isize, isize); 6] = [(-1, -1), (-1, 0), (0, 1), (1, 0), (1, -1), (0, -1)];
+const HEX_ODD_ROW: [(isize, isize); 6] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (0, -1)];
+
+impl Adjacency {
+    pub fn neighbours(&self, coord: Coord, grid: &Grid) -> Vec<Coord> {
+        self.cell_neighbours(coord.cell(), grid.rows(), grid.cols()).into_iter().map(Coord::from_cell).collect()
+    }
+
+    pub fn are_adjacent(&self, a: Coord, b: Coord, grid: &Grid) -> bool {
+        self.cells_adjacent(a.cell(), b.cell(), grid.rows(), grid.cols())
+    }
+
+    // The solver's own (isize, isize) form, without building a Grid
+    pub(crate) fn cell_neighbours(&self, cell: (isize, isize), rows: usize, cols: usize) -> Vec<(isize, isize)> {
+        let (row, col) = cell;
+        let (rows, cols) = (rows as isize, cols as isize);
+        let offsets: &[(isize, isize)] = match self {
+            Adjacency::Four => &FOUR,
+            Adjacency::Eight | Adjacency::Torus => &EIGHT,
+            Adjacency::Hex if row.rem_euclid(2) == 0 => &HEX_EVEN_ROW,
+            Adjacency::Hex => &HEX_ODD_ROW,
+        };
+        let mut result = vec![];
+        for &(dir_row, dir_col) in offsets {
+            let (mut next_row, mut next_col) = (row + dir_row, col + dir_col);
+            if *self == Adjacency::Torus {
+                next_row = next_row.rem_euclid(rows);
+                next_col = next_col.rem_euclid(cols);
+            }
+            if next_row < 0 || next_col < 0 || next_row >= rows || next_col >= cols {
+                continue;
+            }
+            // wrapping on a grid only one or two cells wide can reach the same cell twice, or the cell itself
+            if (next_row, next_col) != cell && !result.contains(&(next_row, next_col)) {
+                result.push((next_row, next_col));
+            }
+        }
+        result
+    }
+
+    pub(crate) fn cells_adjacent(&self, a: (isize, isize), b: (isize, isize), rows: usize, cols: usize) -> bool {
+        self.cell_neighbours(a, rows, cols).contains(&b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Adjacency;
+    use crate::model::{Coord, Grid};
+
+    #[test]
+    fn test_four() {
+        let mut neighbours = Adjacency::Four.cell_neighbours((1, 1), 3, 3);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+        assert_eq!(Adjacency::Four.cell_neighbours((0, 0), 3, 3).len(), 2);
+        assert!(!Adjacency::Four.cells_adjacent((0, 0), (1, 1), 3, 3));
+    }
+
+    #[test]
+    fn test_eight() {
+        assert_eq!(Adjacency::Eight.cell_neighbours((1, 1), 3, 3).len(), 8);
+        assert_eq!(Adjacency::Eight.cell_neighbours((0, 0), 3, 3).len(), 3);
+        assert!(Adjacency::Eight.cells_adjacent((0, 0), (1, 1), 3, 3));
+        assert!(!Adjacency::Eight.cells_adjacent((0, 0), (0, 2), 3, 3));
+    }
+
+    #[test]
+    fn test_hex() {
+        // even rows reach up-left and down-left, odd rows up-right and down-right
+        let mut even = Adjacency::Hex.cell_neighbours((2, 2), 5, 5);
+        even.sort();
+        assert_eq!(even, vec![(1, 1), (1, 2), (2, 1), (2, 3), (3, 1), (3, 2)]);
+        let mut odd = Adjacency::Hex.cell_neighbours((1, 2), 5, 5);
+        odd.sort();
+        assert_eq!(odd, vec![(0, 2), (0, 3), (1, 1), (1, 3), (2, 2), (2, 3)]);
+        assert!(!Adjacency::Hex.cells_adjacent((2, 2), (1, 3), 5, 5));
+    }
+
+    #[test]
+    fn test_torus() {
+        assert_eq!(Adjacency::Torus.cell_neighbours((0, 0), 4, 4).len(), 8);
+        assert!(Adjacency::Torus.cells_adjacent((0, 0), (3, 3), 4, 4));
+        assert!(Adjacency::Torus.cells_adjacent((0, 0), (0, 3), 4, 4));
+        // on a 2-wide grid left and right are the same cell
+        assert_eq!(Adjacency::Torus.cell_neighbours((0, 0), 1, 2), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_coords() {
+        let grid = Grid::parse("ABC\nDEF\nGHI");
+        let mut neighbours = Adjacency::Four.neighbours(Coord::new(1, 1), &grid);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![Coord::new(0, 1), Coord::new(1, 0), Coord::new(1, 2), Coord::new(2, 1)]);
+        assert!(Adjacency::Eight.are_adjacent(Coord::new(0, 0), Coord::new(1, 1), &grid));
+        assert!(!Adjacency::Eight.are_adjacent(Coord::new(0, 0), Coord::new(0, 2), &grid));
+    }
+}
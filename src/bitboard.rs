@@ -0,0 +1,191 @@
+// Bitmask set of grid cells, indexed row-major (row * cols + col).
+// A standard Strands board is 6x8 = 48 cells, so nearly everything fits in a single u64.
+// Boards over 64 cells fall back to a vector of words.
+
+const WORD_BITS: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CellSet {
+    Small(u64),
+    Wide(Vec<u64>),
+}
+
+impl CellSet {
+    pub fn empty(num_cells: usize) -> Self {
+        // picks the representation based on board size, so every set for the same board uses the same variant
+        if num_cells <= WORD_BITS {
+            CellSet::Small(0)
+        } else {
+            CellSet::Wide(vec![0; num_cells.div_ceil(WORD_BITS)])
+        }
+    }
+
+    pub fn full(num_cells: usize) -> Self {
+        let mut set = Self::empty(num_cells);
+        for index in 0..num_cells {
+            set.insert(index);
+        }
+        set
+    }
+
+    pub fn from_indices(num_cells: usize, indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut set = Self::empty(num_cells);
+        for index in indices {
+            set.insert(index);
+        }
+        set
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        match self {
+            CellSet::Small(bits) => *bits |= 1 << index,
+            CellSet::Wide(words) => words[index / WORD_BITS] |= 1 << (index % WORD_BITS),
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        match self.words().get(index / WORD_BITS) {
+            Some(word) => word & (1 << (index % WORD_BITS)) != 0,
+            None => false,
+        }
+    }
+
+    pub fn first_missing(&self, full: &CellSet) -> Option<usize> {
+        // lowest cell of `full` that isn't in this set
+        for (word_index, (word, full_word)) in self.words().iter().zip(full.words()).enumerate() {
+            let missing = full_word & !word;
+            if missing != 0 {
+                return Some(word_index * WORD_BITS + missing.trailing_zeros() as usize);
+            }
+        }
+        None
+    }
+
+    pub fn overlaps(&self, other: &CellSet) -> bool {
+        self.words().iter().zip(other.words()).any(|(a, b)| a & b != 0)
+    }
+
+    pub fn is_subset(&self, other: &CellSet) -> bool {
+        self.words().iter().zip(other.words()).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn union_with(&mut self, other: &CellSet) {
+        self.zip_apply(other, |a, b| a | b);
+    }
+
+    pub fn difference_with(&mut self, other: &CellSet) {
+        self.zip_apply(other, |a, b| a & !b);
+    }
+
+    pub fn count(&self) -> usize {
+        self.words().iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        // set cell indices in ascending order
+        self.words().iter().enumerate().flat_map(|(word_index, word)| {
+            let mut remaining = *word;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(word_index * WORD_BITS + bit)
+            })
+        })
+    }
+
+    fn words(&self) -> &[u64] {
+        match self {
+            CellSet::Small(bits) => std::slice::from_ref(bits),
+            CellSet::Wide(words) => words,
+        }
+    }
+
+    fn zip_apply(&mut self, other: &CellSet, op: impl Fn(u64, u64) -> u64) {
+        match (self, other) {
+            (CellSet::Small(a), CellSet::Small(b)) => *a = op(*a, *b),
+            (CellSet::Wide(a), CellSet::Wide(b)) => {
+                for (a, b) in a.iter_mut().zip(b) {
+                    *a = op(*a, *b);
+                }
+            }
+            _ => panic!("Cell sets from boards of different sizes."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CellSet;
+
+    #[test]
+    fn test_empty_small() {
+        assert_eq!(CellSet::empty(48), CellSet::Small(0));
+    }
+
+    #[test]
+    fn test_empty_wide() {
+        assert_eq!(CellSet::empty(65), CellSet::Wide(vec![0, 0]));
+    }
+
+    #[test]
+    fn test_contains() {
+        let set = CellSet::from_indices(100, [3, 64, 99]);
+        assert!(set.contains(64));
+        assert!(set.contains(99));
+        assert!(!set.contains(63));
+    }
+
+    #[test]
+    fn test_count_iter() {
+        let set = CellSet::from_indices(100, [3, 64, 99]);
+        assert_eq!(set.count(), 3);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 64, 99]);
+        assert_eq!(CellSet::from_indices(48, [47, 0, 5]).iter().collect::<Vec<_>>(), vec![0, 5, 47]);
+    }
+
+    #[test]
+    fn test_is_subset() {
+        let region = CellSet::from_indices(9, [0, 1, 3, 4]);
+        assert!(CellSet::from_indices(9, [0, 4]).is_subset(&region));
+        assert!(!CellSet::from_indices(9, [0, 2]).is_subset(&region));
+    }
+
+    #[test]
+    fn test_first_missing() {
+        let full = CellSet::full(70);
+        assert_eq!(CellSet::from_indices(70, [0, 1, 3]).first_missing(&full), Some(2));
+        assert_eq!(CellSet::from_indices(70, 0..66).first_missing(&full), Some(66));
+        assert_eq!(full.first_missing(&full), None);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let a = CellSet::from_indices(9, [0, 1, 2]);
+        let b = CellSet::from_indices(9, [2, 3]);
+        let c = CellSet::from_indices(9, [4, 5]);
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&c));
+    }
+
+    #[test]
+    fn test_full_coverage() {
+        let mut used = CellSet::empty(4);
+        used.union_with(&CellSet::from_indices(4, [0, 1]));
+        assert_ne!(used, CellSet::full(4));
+        used.union_with(&CellSet::from_indices(4, [2, 3]));
+        assert_eq!(used, CellSet::full(4));
+        used.difference_with(&CellSet::from_indices(4, [1]));
+        assert_eq!(used, CellSet::from_indices(4, [0, 2, 3]));
+    }
+
+    #[test]
+    fn test_full_wide() {
+        let mut used = CellSet::from_indices(70, 0..64);
+        assert!(!used.overlaps(&CellSet::from_indices(70, [64, 69])));
+        used.union_with(&CellSet::from_indices(70, 64..70));
+        assert_eq!(used, CellSet::full(70));
+    }
+}
@@ -0,0 +1,150 @@
+// Word frequencies, used to prefer tilings of everyday words over obscure ones ("aal", "zarf").
+// Loaded from a local list with one `word count` pair per line (space, tab or comma separated).
+// Counts can be raw counts or relative frequencies (0.0004). Raw counts are used as they are ("zarf 3" scores ln 3 less
+// the rare penalty); a list of fractions is scaled up so its rarest word scores like a count of 1.
+// Case agnostic, like the trie.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, PartialEq)]
+pub struct Frequencies {
+    counts: HashMap<String, f64>,
+    smallest: f64, // smallest positive count, so lists of fractions can be scaled up to counts
+    largest: f64, // largest count, the top of the `commonness` scale
+    pub rare_below: f64, // words seen fewer times than this are penalised as rare
+    pub rare_penalty: f64,
+    pub unknown_penalty: f64, // score for words missing from the list
+}
+
+impl Default for Frequencies {
+    fn default() -> Self {
+        Frequencies {
+            counts: HashMap::new(),
+            smallest: f64::INFINITY,
+            largest: 0.0,
+            rare_below: 100.0,
+            rare_penalty: 5.0,
+            unknown_penalty: 10.0,
+        }
+    }
+}
+
+impl Frequencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        // lines that don't parse are skipped, so headers and comments are fine
+        let mut frequencies = Self::new();
+        for line in text.lines() {
+            let mut parts = line.split(|c: char| c.is_whitespace() || c == ',').filter(|part| !part.is_empty());
+            if let (Some(word), Some(count), None) = (parts.next(), parts.next(), parts.next()) {
+                if let Ok(count) = count.parse() {
+                    frequencies.insert(word, count);
+                }
+            }
+        }
+        frequencies
+    }
+
+    pub fn insert(&mut self, word: &str, count: f64) {
+        if count > 0.0 {
+            self.smallest = self.smallest.min(count);
+            self.largest = self.largest.max(count);
+        }
+        self.counts.insert(word.to_ascii_lowercase(), count);
+    }
+
+    pub fn word_score(&self, word: &str) -> f64 {
+        // log of the word's count, minus a penalty if it's rare. Higher is more common.
+        match self.counts.get(&word.to_ascii_lowercase()) {
+            Some(&count) => self.word_score_of(count),
+            None => -self.unknown_penalty,
+        }
+    }
+
+    fn word_score_of(&self, count: f64) -> f64 {
+        if count <= 0.0 {
+            return -self.unknown_penalty;
+        }
+        // counts are only ever scaled up, so lists of raw counts score as they are
+        let count = count * (1.0 / self.smallest).max(1.0);
+        let penalty = if count < self.rare_below { self.rare_penalty } else { 0.0 };
+        count.ln() - penalty
+    }
+
+    pub fn score<'a>(&self, words: impl IntoIterator<Item = &'a String>) -> f64 {
+        words.into_iter().map(|word| self.word_score(word)).sum()
+    }
+
+    pub fn commonness<'a>(&self, words: impl IntoIterator<Item = &'a String>) -> f64 {
+        // `score` with each word's score scaled to between 0 (unknown) and 1 (the most common listed word),
+        // so it can be added to other per word scores without drowning them out
+        let range = self.word_score_of(self.largest) + self.unknown_penalty;
+        if range <= 0.0 {
+            return 0.0;
+        }
+        words
+            .into_iter()
+            .map(|word| (self.word_score(word) + self.unknown_penalty) / range)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Frequencies;
+
+    #[test]
+    fn test_parse() {
+        let frequencies = Frequencies::parse("word count\nThe 5000\ncat,120\nzarf\t3\n");
+        let mut good = Frequencies::new();
+        good.insert("the", 5000.0);
+        good.insert("cat", 120.0);
+        good.insert("zarf", 3.0);
+        assert_eq!(frequencies, good);
+    }
+
+    #[test]
+    fn test_word_score() {
+        let frequencies = Frequencies::parse("cat 1000\nzarf 3");
+        assert_eq!(frequencies.word_score("CAT"), 1000f64.ln());
+        assert_eq!(frequencies.word_score("zarf"), 3f64.ln() - 5.0);
+        assert_eq!(frequencies.word_score("aal"), -10.0);
+        assert!(frequencies.word_score("cat") > frequencies.word_score("zarf"));
+        assert!(frequencies.word_score("zarf") > frequencies.word_score("aal"));
+    }
+
+    #[test]
+    fn test_word_score_fractions() {
+        // relative frequencies score like the counts they're proportional to
+        let fractions = Frequencies::parse("cat 0.01\nzarf 0.00003\nnil 0");
+        let counts = Frequencies::parse("cat 1000\nzarf 3");
+        assert_eq!(fractions.word_score("zarf"), -5.0);
+        assert!((fractions.word_score("cat") - fractions.word_score("zarf") - (counts.word_score("cat") - counts.word_score("zarf"))).abs() < 1e-9);
+        assert!(fractions.word_score("zarf") > fractions.word_score("aal"));
+        // a count of 0 says nothing about how common the word is
+        assert_eq!(fractions.word_score("nil"), -10.0);
+    }
+
+    #[test]
+    fn test_commonness() {
+        let frequencies = Frequencies::parse("cat 1000\nzarf 3");
+        let words = |words: &[&str]| words.iter().map(|word| word.to_string()).collect::<Vec<_>>();
+        assert_eq!(frequencies.commonness(&words(&["cat"])), 1.0);
+        assert_eq!(frequencies.commonness(&words(&["aal"])), 0.0);
+        let zarf = frequencies.commonness(&words(&["zarf"]));
+        assert!(0.0 < zarf && zarf < 1.0);
+        assert_eq!(frequencies.commonness(&words(&["cat", "zarf", "aal"])), 1.0 + zarf);
+        // an empty list knows no words
+        assert_eq!(Frequencies::new().commonness(&words(&["cat"])), 0.0);
+    }
+}
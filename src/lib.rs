@@ -0,0 +1,7 @@
+pub mod adjacency;
+pub mod bitboard;
+pub mod frequency;
+pub mod model;
+pub mod strands;
+pub mod theme;
+pub mod trie;
@@ -1,14 +1,49 @@
+use std::env;
 use std::fs;
 use std::path::Path;
 use std::io::{self, BufRead};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::strands::Strands;
+use strands_solver::frequency::Frequencies;
+use strands_solver::model::Placement;
+use strands_solver::strands::{SearchStats, Rules, SolveObserver, SolveOptions, SolveOutcome, Strands};
+use strands_solver::theme::Associations;
+use strands_solver::trie;
 
-mod trie;
-mod strands;
+const RANKING_TIMEOUT: Duration = Duration::from_secs(10); // how long to look for the best tiling by theme and word frequency
+const ADVISED_TILINGS: usize = 1000; // how many tilings to weigh when suggesting the next word to try
+const EXPLAIN_BRANCHES: usize = 5; // rejected placements shown per step with `--explain`
+
+// Prints what the solver finds along the way
+struct PrintObserver;
+
+impl SolveObserver for PrintObserver {
+    fn candidates_found(&self, candidates: &[Placement]) {
+        println!("Found {:?} potential words.", candidates.len());
+        let mut word_list: Vec<&String> = candidates.iter().map(|placement| &placement.word).collect();
+        word_list.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        word_list.dedup();
+        println!("Words: ");
+        for word in word_list {
+            println!("{:?}", word);
+        }
+    }
+
+    fn finished(&self, stats: &SearchStats) {
+        println!(
+            "Searched {} placements, pruned {} small regions and {} unfillable regions.",
+            stats.nodes, stats.small_regions, stats.unfillable_regions
+        );
+    }
+}
 
 fn main() {
-    let file_path = Path::new("./english-words/words_alpha_pruned.txt"); //using a custom dict. just english words with len <4 removed.
+    // words shorter than the game allows are skipped by the solver, so the full list and the pruned one give the same answers
+    let file_path = [Path::new("./english-words/words_alpha_pruned.txt"), Path::new("./english-words/words_alpha.txt")]
+        .into_iter()
+        .find(|path| path.exists())
+        .unwrap_or(Path::new("./english-words/words_alpha.txt"));
     println!("Reading file {}...", file_path.to_str().unwrap());
     let file = fs::File::open(file_path).expect("Failed to open file.");
     let reader = io::BufReader::new(file);
@@ -23,15 +58,109 @@ fn main() {
     let file_path = Path::new("./strands.txt");
     println!("Reading file {}...", file_path.to_str().unwrap());
     let puzzle = fs::read_to_string(file_path).expect("Unable to read puzzle.");
-    let strands = Strands::new(puzzle, 8);
+    let args: Vec<String> = env::args().skip(1).collect();
+    // `--explain` prints the placements the solver tried and why they were rejected, `--explain=json` does the same as JSON
+    let explain = args.iter().find_map(|arg| match arg.as_str() {
+        "--explain" => Some(false),
+        "--explain=json" => Some(true),
+        _ => None,
+    });
+    // optional argument: the number of answers ("8"), or a range when it isn't known exactly ("6-9")
+    let mut strands = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(arg) => match arg.split_once('-') {
+            Some((min, max)) => Strands::new(puzzle, None).with_answer_range(
+                min.parse().expect("Invalid minimum number of answers."),
+                max.parse().expect("Invalid maximum number of answers."),
+            ),
+            None => Strands::new(puzzle, arg.parse::<usize>().expect("Invalid number of answers.")),
+        },
+        None => Strands::new(puzzle, None),
+    };
+    // answers in the real game are four letters or longer and never cross each other diagonally
+    strands = strands.with_rules(Rules::strands());
+    // words the game already rejected, one per line
+    let file_path = Path::new("./excluded.txt");
+    if let Ok(excluded) = fs::read_to_string(file_path) {
+        println!("Reading file {}...", file_path.to_str().unwrap());
+        for word in excluded.lines().map(str::trim).filter(|word| !word.is_empty()) {
+            strands.excluded.exclude_word(word);
+        }
+    }
+    // theme clue and a local word association file, used together to pick the most thematic tiling
+    let file_path = Path::new("./clue.txt");
+    if let Ok(clue) = fs::read_to_string(file_path) {
+        println!("Reading file {}...", file_path.to_str().unwrap());
+        strands = strands.with_clue(clue.trim());
+    }
+    let file_path = Path::new("./associations.txt");
+    let associations = match strands.clue {
+        Some(_) if file_path.exists() => {
+            println!("Reading file {}...", file_path.to_str().unwrap());
+            Some(Associations::load(file_path).expect("Unable to read word associations."))
+        }
+        _ => None,
+    };
+    // word frequencies, used to prefer tilings of common words
+    let file_path = Path::new("./frequencies.txt");
+    let frequencies = if file_path.exists() {
+        println!("Reading file {}...", file_path.to_str().unwrap());
+        Some(Frequencies::load(file_path).expect("Unable to read word frequencies."))
+    } else {
+        None
+    };
     println!("Puzzle loaded:");
     strands.print();
     println!("Attempting to solve...");
-    if let Some(results) = strands.solve(&trie) {
-        println!("Solution:\n");
-        for row in results.keys() {
-            println!("{}", row);
+    if let Some(json) = explain {
+        let trace = strands.explain(&trie, &SolveOptions::new(), EXPLAIN_BRANCHES);
+        if json {
+            println!("{}", trace.to_json());
+        } else {
+            println!("{}", trace);
+        }
+        return;
+    }
+    let solution = if associations.is_some() || frequencies.is_some() {
+        // rank tilings by theme and word frequency together, both on a per word scale of about 0 to 1
+        // (a theme hit per related answer, commonness from unknown to the most common listed word)
+        let options = SolveOptions::new().with_timeout(RANKING_TIMEOUT);
+        let ranking = strands.solve_ranked_with_options(&trie, &options, 1, |solution| {
+            let theme = associations.as_ref().map_or(0.0, |associations| strands.theme_score(solution, associations));
+            let frequency = frequencies.as_ref().map_or(0.0, |frequencies| frequencies.commonness(solution.words()));
+            theme + frequency
+        });
+        if !ranking.exhaustive {
+            println!("Ran out of time after ranking the first {} tilings found, a better one may be left.\n", ranking.tilings);
+        }
+        ranking.solutions.into_iter().next()
+    } else {
+        let options = SolveOptions::new().with_observer(Arc::new(PrintObserver));
+        match strands.solve_with_options(&trie, &options) {
+            SolveOutcome::Solved(solution) => Some(solution),
+            _ => None,
+        }
+    };
+    if let Some(solution) = solution {
+        match solution.score {
+            Some(score) => println!("Solution ({} words, score {:.2}):\n", solution.len(), score),
+            None => println!("Solution ({} words):\n", solution.len()),
+        }
+        for word in solution.words() {
+            println!("{}", word);
+        }
+        for spangram in solution.spangrams() {
+            println!("\nSpangram: {}", spangram.word);
+        }
+        if strands.count_solutions(&trie, 2) > 1 {
+            println!("\nWarning: this board has more than one solution with the current dictionary.");
+            let advice = strands.advise(&trie, &SolveOptions::new(), ADVISED_TILINGS);
+            if let Some(best) = advice.best() {
+                let sampled = if advice.exhaustive { "" } else { " looked at" };
+                println!("Most informative word to try next: {} (used by {} of the {} tilings{}).", best.placement.word, best.tilings, advice.tilings, sampled);
+            }
         }
+    } else if strands.unsolvable_due_to_exclusions(&trie) {
+        print!("No solution found! The board is only solvable with some of the excluded words.");
     } else {
         print!("No solution found!");
     }
@@ -0,0 +1,399 @@
+// Typed building blocks for boards and answers, so callers don't juggle raw tuples and nested vectors.
+// The solver works on (isize, isize) pairs internally and converts at the edges.
+
+use std::fmt;
+use std::ops::Index;
+
+// Marks a cell that isn't part of the board. Never traversed and doesn't need covering.
+pub const BLOCKED: &str = ".";
+
+// Largest number of rows or columns `Grid::try_parse` accepts. Real boards are 6x8,
+// and the word search gets impractically slow long before this.
+pub const MAX_SIDE: usize = 32;
+
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Copy)]
+pub struct Coord {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coord {
+    pub fn new(row: usize, col: usize) -> Self {
+        Coord { row, col }
+    }
+
+    pub(crate) fn from_cell(cell: (isize, isize)) -> Self {
+        // only for cells already known to be on the board
+        Coord::new(cell.0 as usize, cell.1 as usize)
+    }
+
+    pub(crate) fn cell(self) -> (isize, isize) {
+        (self.row as isize, self.col as isize)
+    }
+}
+
+impl From<(usize, usize)> for Coord {
+    fn from((row, col): (usize, usize)) -> Self {
+        Coord::new(row, col)
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum GridError {
+    Empty, // no rows, or no open cells
+    RaggedRow { line: usize, len: usize, expected: usize }, // lines count from 1, like in an editor
+    InvalidChar { line: usize, col: usize, found: char },
+    TooLarge { rows: usize, cols: usize },
+    InvalidTile(String), // a single tile that isn't letters or `.`
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "The grid has no letters."),
+            GridError::RaggedRow { line, len, expected } => {
+                write!(f, "Row {} has {} tiles, but the first row has {}.", line, len, expected)
+            }
+            GridError::InvalidChar { line, col, found } => write!(f, "Invalid character {:?} in row {}, column {}.", found, line, col),
+            GridError::TooLarge { rows, cols } => write!(f, "The grid is {}x{}, at most {}x{} is supported.", rows, cols, MAX_SIDE, MAX_SIDE),
+            GridError::InvalidTile(tile) => write!(f, "Invalid tile {:?}, expected one or more letters or \".\".", tile),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+// A rectangular board of tiles, one per cell. Usually a single letter, but a tile can hold several ("QU").
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Grid {
+    tiles: Vec<Vec<String>>,
+}
+
+impl Grid {
+    pub fn parse(input: &str) -> Self {
+        // One row per line. `.` or a space is a blocked cell, and a tile of several letters is written in brackets, `[QU]`.
+        // Short rows are padded out with blocked cells, so the grid is always rectangular.
+        let mut tiles: Vec<Vec<String>> = input.lines().map(Self::parse_tiles).collect();
+        let cols = tiles.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in &mut tiles {
+            row.resize(cols, BLOCKED.to_string());
+        }
+        Grid { tiles }
+    }
+
+    fn parse_tiles(line: &str) -> Vec<String> {
+        // an unclosed bracket is read as a plain letter
+        let mut tiles = vec![];
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '[' {
+                if let Some((tile, rest)) = chars.as_str().split_once(']') {
+                    if !tile.is_empty() {
+                        tiles.push(tile.to_string());
+                        chars = rest.chars();
+                        continue;
+                    }
+                }
+            }
+            tiles.push(if c == ' ' { BLOCKED.to_string() } else { c.to_string() });
+        }
+        tiles
+    }
+
+    pub fn try_parse(input: &str) -> Result<Self, GridError> {
+        // The strict version of `parse`, for input from users.
+        // Every row needs the same number of tiles, blocked cells have to be written as `.`,
+        // and letters are upper-cased. Blank lines before and after the grid and trailing whitespace are ignored.
+        let lines: Vec<&str> = input.lines().map(str::trim_end).collect();
+        let first = lines.iter().position(|line| !line.is_empty()).ok_or(GridError::Empty)?;
+        let last = lines.iter().rposition(|line| !line.is_empty()).unwrap_or(first);
+        let mut tiles = vec![];
+        for (index, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+            tiles.push(Self::try_parse_tiles(line, index + 1)?);
+        }
+
+        let expected = tiles[0].len();
+        if let Some(index) = tiles.iter().position(|row| row.len() != expected) {
+            return Err(GridError::RaggedRow { line: first + index + 1, len: tiles[index].len(), expected });
+        }
+        if tiles.len() > MAX_SIDE || expected > MAX_SIDE {
+            return Err(GridError::TooLarge { rows: tiles.len(), cols: expected });
+        }
+        if tiles.iter().flatten().all(|tile| tile == BLOCKED) {
+            return Err(GridError::Empty);
+        }
+        Ok(Grid { tiles })
+    }
+
+    fn try_parse_tiles(line: &str, line_number: usize) -> Result<Vec<String>, GridError> {
+        // columns are counted in characters, so an error can be found in the input file
+        let invalid = |col: usize, found: char| GridError::InvalidChar { line: line_number, col: col + 1, found };
+        let mut tiles = vec![];
+        let mut chars = line.chars().enumerate();
+        while let Some((col, c)) = chars.next() {
+            match c {
+                '.' => tiles.push(BLOCKED.to_string()),
+                '[' => {
+                    let mut tile = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, ']')) if !tile.is_empty() => break,
+                            Some((_, letter)) if letter.is_ascii_alphabetic() => tile.push(letter.to_ascii_uppercase()),
+                            Some((col, found)) => return Err(invalid(col, found)),
+                            None => return Err(invalid(col, '[')), // never closed
+                        }
+                    }
+                    tiles.push(tile);
+                }
+                c if c.is_ascii_alphabetic() => tiles.push(c.to_ascii_uppercase().to_string()),
+                found => return Err(invalid(col, found)),
+            }
+        }
+        Ok(tiles)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.tiles.first().map_or(0, |row| row.len())
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.rows() * self.cols()
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.row < self.rows() && coord.col < self.cols()
+    }
+
+    pub fn coord(&self, row: isize, col: isize) -> Option<Coord> {
+        // the bounds-checked way to turn signed coordinate math back into a cell
+        let coord = Coord::new(usize::try_from(row).ok()?, usize::try_from(col).ok()?);
+        self.contains(coord).then_some(coord)
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&str> {
+        self.tiles.get(coord.row)?.get(coord.col).map(String::as_str)
+    }
+
+    pub fn is_blocked(&self, coord: Coord) -> bool {
+        // cells off the board count as blocked
+        self.get(coord).is_none_or(|tile| tile == BLOCKED)
+    }
+
+    pub fn index(&self, coord: Coord) -> usize {
+        // row-major, matching the layout `CellSet` expects
+        coord.row * self.cols() + coord.col
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = Coord> + '_ {
+        // every cell in reading order, blocked ones included
+        (0..self.rows()).flat_map(move |row| (0..self.cols()).map(move |col| Coord::new(row, col)))
+    }
+
+    pub fn tile_rows(&self) -> impl Iterator<Item = &[String]> {
+        self.tiles.iter().map(Vec::as_slice)
+    }
+
+    pub fn set(&mut self, coord: Coord, tile: &str) -> Result<(), GridError> {
+        // Replaces one cell's tile, `.` blocks it. Panics if `coord` is off the board, like indexing does.
+        // Checked like `try_parse` checks a tile: letters only, upper-cased, and at least one of them.
+        if tile != BLOCKED && (tile.is_empty() || !tile.chars().all(|c| c.is_ascii_alphabetic())) {
+            return Err(GridError::InvalidTile(tile.to_string()));
+        }
+        self.tiles[coord.row][coord.col] = tile.to_ascii_uppercase();
+        Ok(())
+    }
+}
+
+impl Index<Coord> for Grid {
+    type Output = String;
+
+    fn index(&self, coord: Coord) -> &String {
+        &self.tiles[coord.row][coord.col]
+    }
+}
+
+// The cells a word is traced through, in order
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Default)]
+pub struct Path(Vec<Coord>);
+
+impl Path {
+    pub fn new(coords: Vec<Coord>) -> Self {
+        Path(coords)
+    }
+
+    pub fn coords(&self) -> &[Coord] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        self.0.contains(&coord)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Coord> {
+        self.0.iter()
+    }
+
+    pub(crate) fn from_cells(cells: &[(isize, isize)]) -> Self {
+        Path(cells.iter().map(|&cell| Coord::from_cell(cell)).collect())
+    }
+
+    pub(crate) fn cells(&self) -> Vec<(isize, isize)> {
+        self.0.iter().map(|coord| coord.cell()).collect()
+    }
+}
+
+impl From<Vec<(usize, usize)>> for Path {
+    fn from(coords: Vec<(usize, usize)>) -> Self {
+        Path(coords.into_iter().map(Coord::from).collect())
+    }
+}
+
+impl PartialEq<Vec<(usize, usize)>> for Path {
+    fn eq(&self, other: &Vec<(usize, usize)>) -> bool {
+        self.0.len() == other.len() && self.0.iter().zip(other).all(|(coord, &other)| *coord == Coord::from(other))
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Placement {
+    pub word: String,
+    pub path: Path,
+    pub spangram: bool,
+}
+
+// A full set of answers, ordered by where each path starts in reading order
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct Solution {
+    pub placements: Vec<Placement>,
+    pub score: Option<f64>, // set by the ranked solvers, higher is better
+}
+
+impl Solution {
+    pub fn new(mut placements: Vec<Placement>) -> Self {
+        placements.sort_by(|a, b| (&a.path, &a.word).cmp(&(&b.path, &b.word)));
+        Solution { placements, score: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.placements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = &String> {
+        self.placements.iter().map(|placement| &placement.word)
+    }
+
+    pub fn get(&self, word: &str) -> Option<&Placement> {
+        self.placements.iter().find(|placement| placement.word == word)
+    }
+
+    pub fn contains_word(&self, word: &str) -> bool {
+        self.get(word).is_some()
+    }
+
+    pub fn spangrams(&self) -> Vec<&Placement> {
+        // a valid Strands solution has exactly one
+        self.placements.iter().filter(|placement| placement.spangram).collect()
+    }
+}
+
+impl Index<&str> for Solution {
+    type Output = Path;
+
+    fn index(&self, word: &str) -> &Path {
+        &self.get(word).expect("word not in solution").path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coord, Grid, GridError, Path, Placement, Solution, MAX_SIDE};
+
+    #[test]
+    fn test_grid_parse() {
+        let grid = Grid::parse("[QU]IT\nA B\nC");
+        assert_eq!((grid.rows(), grid.cols()), (3, 3));
+        assert_eq!(grid.get(Coord::new(0, 0)), Some("QU"));
+        assert_eq!(grid.get(Coord::new(0, 3)), None);
+        assert!(grid.is_blocked(Coord::new(1, 1)));
+        assert!(grid.is_blocked(Coord::new(2, 2)));
+        assert!(grid.is_blocked(Coord::new(5, 0)));
+        assert!(!grid.is_blocked(Coord::new(2, 0)));
+        assert_eq!(grid.index(Coord::new(2, 1)), 7);
+    }
+
+    #[test]
+    fn test_grid_try_parse() {
+        let grid = Grid::try_parse("\nab.\n[qu]Xy  \n\n").unwrap();
+        assert_eq!(grid, Grid::parse("AB.\n[QU]XY"));
+        assert_eq!(Grid::try_parse(""), Err(GridError::Empty));
+        assert_eq!(Grid::try_parse(" \n\n"), Err(GridError::Empty));
+        assert_eq!(Grid::try_parse("..\n.."), Err(GridError::Empty));
+        assert_eq!(Grid::try_parse("ABC\nDE\nFGH"), Err(GridError::RaggedRow { line: 2, len: 2, expected: 3 }));
+        assert_eq!(Grid::try_parse("ABC\n\nDEF"), Err(GridError::RaggedRow { line: 2, len: 0, expected: 3 }));
+        assert_eq!(Grid::try_parse("ABC\nD F"), Err(GridError::InvalidChar { line: 2, col: 2, found: ' ' }));
+        assert_eq!(Grid::try_parse("A1C"), Err(GridError::InvalidChar { line: 1, col: 2, found: '1' }));
+        assert_eq!(Grid::try_parse("A[Q"), Err(GridError::InvalidChar { line: 1, col: 2, found: '[' }));
+        assert_eq!(Grid::try_parse("A[]"), Err(GridError::InvalidChar { line: 1, col: 3, found: ']' }));
+        assert_eq!(Grid::try_parse("Aé"), Err(GridError::InvalidChar { line: 1, col: 2, found: 'é' }));
+        let wide = "A".repeat(MAX_SIDE + 1);
+        assert_eq!(Grid::try_parse(&wide), Err(GridError::TooLarge { rows: 1, cols: MAX_SIDE + 1 }));
+    }
+
+    #[test]
+    fn test_grid_set() {
+        let mut grid = Grid::parse("AB\nCD");
+        assert_eq!(grid.set(Coord::new(0, 1), "qu"), Ok(()));
+        assert_eq!(grid.set(Coord::new(1, 0), "."), Ok(()));
+        assert_eq!(grid, Grid::parse("A[QU]\n.D"));
+        for tile in ["", " ", "A1", "[Q]", "é"] {
+            assert_eq!(grid.set(Coord::new(1, 1), tile), Err(GridError::InvalidTile(tile.to_string())));
+        }
+        assert_eq!(grid[Coord::new(1, 1)], "D");
+    }
+
+    #[test]
+    fn test_grid_coord() {
+        let grid = Grid::parse("AB\nCD");
+        assert_eq!(grid.coord(1, 0), Some(Coord::new(1, 0)));
+        assert_eq!(grid.coord(-1, 0), None);
+        assert_eq!(grid.coord(0, 2), None);
+        assert_eq!(grid.coords().collect::<Vec<_>>(), vec![
+            Coord::new(0, 0), Coord::new(0, 1), Coord::new(1, 0), Coord::new(1, 1)
+        ]);
+    }
+
+    #[test]
+    fn test_solution_order() {
+        let placement = |word: &str, path: Vec<(usize, usize)>| Placement { word: word.to_string(), path: Path::from(path), spangram: false };
+        let solution = Solution::new(vec![
+            placement("DOG", vec![(1, 0), (1, 1)]),
+            placement("CAT", vec![(0, 1), (0, 0)]),
+        ]);
+        assert_eq!(solution.words().collect::<Vec<_>>(), vec!["CAT", "DOG"]);
+        assert_eq!(solution["DOG"], vec![(1, 0), (1, 1)]);
+        assert!(!solution.contains_word("BEE"));
+        assert!(solution.spangrams().is_empty());
+    }
+}
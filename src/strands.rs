@@ -1,326 +1,787 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+use std::ops::AddAssign;
+use std::sync::Arc;
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 // Datastructure for the actual Strands puzzle
+use crate::adjacency::Adjacency;
+use crate::bitboard::CellSet;
+use crate::model::{Coord, Grid, GridError, Path, Placement, Solution};
 use crate::trie::Trie;
 
+mod advice;
+mod cover;
+mod exclude;
+mod explain;
+mod hint;
+mod iter;
+mod locked;
+mod observer;
+mod options;
+mod ranking;
+mod rules;
+mod session;
+mod verify;
+pub use advice::{Advice, MoveScore};
+pub use exclude::Exclusions;
+pub use explain::{Rejection, Trace, TraceNode};
+pub use hint::{Hint, HintPick};
+pub use iter::Solutions;
+pub use locked::{LockError, LockedAnswer};
+pub use observer::{Phase, SolveObserver, PROGRESS_INTERVAL};
+pub use options::{CancelToken, SolveOptions, SolveOutcome, StopReason};
+pub use ranking::Ranking;
+pub use rules::Rules;
+pub use session::Session;
+pub use verify::Violation;
+use cover::{Cursor, Refusal};
+use options::Budget;
+
+// A word and one path it can be traced along, as the search sees it. `Placement` is the public version.
+pub(crate) type Candidate = (String, Vec<(isize, isize)>);
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct Strands {
-    pub puzzle: Vec<Vec<char>>,
-    pub num_answers: usize,
+    pub grid: Grid,
+    pub num_answers: Option<usize>, // None when the day's answer count isn't known
+    pub answer_range: Option<(usize, usize)>, // inclusive bounds on the answer count, only used when `num_answers` is None
+    pub excluded: Exclusions, // candidates the solver must never use
+    pub clue: Option<String>, // the puzzle's theme clue, used to rank alternative tilings
+    pub adjacency: Adjacency, // which cells a path can step between
+    pub rules: Rules, // optional rules a tiling has to follow
+}
+
+// Counters from the solution search, to see how much work the pruning saves
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct SearchStats {
+    pub nodes: usize, // placements tried
+    pub small_regions: usize, // branches cut because an uncovered region was smaller than the shortest candidate
+    pub unfillable_regions: usize, // branches cut because no candidate fits inside an uncovered region
+}
+
+impl AddAssign for SearchStats {
+    fn add_assign(&mut self, other: SearchStats) {
+        self.nodes += other.nodes;
+        self.small_regions += other.small_regions;
+        self.unfillable_regions += other.unfillable_regions;
+    }
+}
+
+// Everything the solution search needs that doesn't change between branches
+struct SolutionSearch {
+    masks: Vec<CellSet>, // the cells each candidate covers
+    crossings: Vec<CellSet>, // the diagonal blocks each candidate steps through, see `crossing_mask`
+    by_cell: Vec<Vec<usize>>, // candidate indices covering each cell
+    neighbours: Vec<Vec<usize>>, // adjacent cell indices of each cell
+    full: CellSet, // every open cell, which a solution has to cover
+    num_cells: usize,
+    num_blocks: usize, // size of every crossing mask
+    min_word_len: usize,
+    bounds: (usize, usize),
+    limit: usize,
+    budget: Arc<Budget>,
+}
+
+// Cancels a parallel branch once the branches before it have found enough solutions, see `branch_search`
+#[cfg(feature = "parallel")]
+struct BranchHook<'a> {
+    branch: usize,
+    enough: &'a AtomicUsize, // the first branch by which the limit is reached, counting every branch before it too
+}
+
+#[cfg(feature = "parallel")]
+impl cover::SearchHook for BranchHook<'_> {
+    fn cancelled(&self) -> bool {
+        self.branch > self.enough.load(Ordering::Relaxed)
+    }
 }
 
 impl Strands {
-    pub fn new(input: String, answers: usize) -> Self {
-        let mut strands = Strands {
-            puzzle: vec![],
-            num_answers: answers,
-        };
-        let mut row = 0;
-        for line in input.lines() {
-            strands.puzzle.push(vec![]);
-            for c in line.chars() {
-                strands.puzzle[row].push(c);
-            }
-            row += 1;
+    pub fn new(input: String, answers: impl Into<Option<usize>>) -> Self {
+        // see `Grid::parse` for the input format
+        Self::from_grid(Grid::parse(&input), answers.into())
+    }
+
+    pub fn parse(input: &str) -> Result<Self, GridError> {
+        // Like `new`, but rejects malformed grids instead of guessing, see `Grid::try_parse`.
+        // The number of answers is unknown, set it with `with_answer_range` if needed.
+        Ok(Self::from_grid(Grid::try_parse(input)?, None))
+    }
+
+    fn from_grid(grid: Grid, num_answers: Option<usize>) -> Self {
+        Strands {
+            grid,
+            num_answers,
+            answer_range: None,
+            excluded: Exclusions::default(),
+            clue: None,
+            adjacency: Adjacency::default(),
+            rules: Rules::default(),
         }
-        strands
+    }
+
+    pub fn with_answer_range(mut self, min: usize, max: usize) -> Self {
+        // Replaces an exact answer count, if there was one: a board has either a count or a range, never both.
+        // Panics if `min` is above `max`, since no tiling could ever fit.
+        assert!(min <= max, "minimum number of answers {} is above the maximum {}", min, max);
+        self.num_answers = None;
+        self.answer_range = Some((min, max));
+        self
+    }
+
+    pub fn with_adjacency(mut self, adjacency: Adjacency) -> Self {
+        self.adjacency = adjacency;
+        self
     }
 
     pub fn print(&self) {
-        for row in &self.puzzle {
-            for c in row {
-                print!(" {} ", *c);
+        for row in self.grid.tile_rows() {
+            for tile in row {
+                print!(" {} ", tile);
             }
-            print!("\n")
+            println!()
         }
     }
 
-    pub fn solve(&self, trie: &Trie) -> Option<HashMap<String, Vec<(isize, isize)>>> {
-        let rows = self.puzzle.len();
-        let cols = self.puzzle[0].len();
-        
-        let mut candidates: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        for row in 0..rows {
-            for col in 0..cols {
-                let mut visited_tracker: Vec<Vec<bool>> = vec![vec![false; cols]; rows];
-                for row2 in row..rows {
-                    for col2 in col..cols {
-                        let starting_point = (row2 as isize, col2 as isize);
-                        let mut guess_word = String::new();
-                        let mut guess_coords = vec![];
-                        if !visited_tracker[row2][col2] {
-                            Self::recurse_find_words(
-                                &self.puzzle,
-                                trie,
-                                starting_point,
-                                &mut visited_tracker,
-                                &mut guess_word,
-                                &mut guess_coords,
-                                &mut candidates,
-                            );
-                        }
+    pub fn is_blocked(&self, coord: Coord) -> bool {
+        self.grid.is_blocked(coord)
+    }
+
+    fn blocked(&self, cell: (isize, isize)) -> bool {
+        // negative cells wrap to huge ones, which are off the board and so blocked too
+        self.grid.is_blocked(Coord::from_cell(cell))
+    }
+
+    fn open_mask(&self) -> CellSet {
+        CellSet::from_indices(
+            self.num_cells(),
+            self.grid.coords().filter(|&coord| !self.is_blocked(coord)).map(|coord| self.grid.index(coord)),
+        )
+    }
+
+    fn num_cells(&self) -> usize {
+        self.grid.num_cells()
+    }
+
+    fn answer_bounds(&self) -> (usize, usize) {
+        // (min, max) number of words a solution may use.
+        // with no count and no range, any exact tiling is accepted; it can't use more words than there are cells
+        match (self.num_answers, self.answer_range) {
+            (Some(answers), _) => (answers, answers),
+            (None, Some((min, max))) => (min, max),
+            (None, None) => (1, self.num_cells()),
+        }
+    }
+
+    pub fn is_spangram(&self, path: &Path) -> bool {
+        // A spangram touches two opposite sides of the board: top and bottom, or left and right.
+        // On irregular boards the sides are the outermost rows and columns with open cells.
+        // The path itself has to be contiguous under the board's adjacency, which every candidate already is.
+        // A board one cell thick has no opposite sides along that axis, so only the other axis counts.
+        let open: Vec<Coord> = self.grid.coords().filter(|&coord| !self.is_blocked(coord)).collect();
+        let spans = |side: fn(&Coord) -> usize| {
+            let (Some(first), Some(last)) = (open.iter().map(side).min(), open.iter().map(side).max()) else {
+                return false;
+            };
+            first != last && path.iter().any(|coord| side(coord) == first) && path.iter().any(|coord| side(coord) == last)
+        };
+        spans(|coord| coord.row) || spans(|coord| coord.col)
+    }
+
+    fn placement(&self, (word, coords): &Candidate) -> Placement {
+        let path = Path::from_cells(coords);
+        Placement { word: word.clone(), spangram: self.is_spangram(&path), path }
+    }
+
+    fn solution(&self, candidates: &[&Candidate]) -> Solution {
+        Solution::new(candidates.iter().map(|candidate| self.placement(candidate)).collect())
+    }
+
+    fn coords_to_mask(&self, coords: &[(isize, isize)]) -> CellSet {
+        // row-major cell indices, matching the layout `CellSet` expects
+        let cols = self.grid.cols();
+        CellSet::from_indices(
+            self.num_cells(),
+            coords.iter().map(|&(row, col)| row as usize * cols + col as usize),
+        )
+    }
+
+    pub fn find_candidates(&self, trie: &Trie) -> Vec<Placement> {
+        // Every dictionary word that can be traced on the board, with the path used.
+        // A word can show up more than once with different paths; paths covering the same cells are only kept once.
+        // Excluded words and paths are dropped here, so the cover search never sees them.
+        self.candidates(trie).iter().map(|candidate| self.placement(candidate)).collect()
+    }
+
+    fn candidates(&self, trie: &Trie) -> Vec<Candidate> {
+        self.find_candidates_within(trie, &Budget::unlimited())
+    }
+
+    fn find_candidates_within(&self, trie: &Trie, budget: &Budget) -> Vec<Candidate> {
+        // stops tracing words once `budget` runs out, leaving the candidate list incomplete
+        let rows = self.grid.rows();
+        let cols = self.grid.cols();
+
+        let starts: Vec<(usize, usize)> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .collect();
+        // start cells are independent, and collecting keeps them in grid order either way
+        #[cfg(feature = "parallel")]
+        let per_start: Vec<_> = starts.par_iter().map(|&start| self.words_from(trie, start, budget).0).collect();
+        #[cfg(not(feature = "parallel"))]
+        let per_start: Vec<_> = starts.iter().map(|&start| self.words_from(trie, start, budget).0).collect();
+        self.filter_candidates(per_start.into_iter().flatten().collect(), budget)
+    }
+
+    fn filter_candidates(&self, mut found: Vec<Candidate>, budget: &Budget) -> Vec<Candidate> {
+        // everything found by walking the board, in grid order, into the list the cover search uses
+        found.retain(|candidate| !self.excluded.is_excluded(candidate));
+        if self.rules.no_crossing {
+            found.retain(|(_, coords)| !self.crosses_itself(coords));
+        }
+        let mut seen = HashSet::new();
+        found.retain(|(word, coords)| seen.insert((word.clone(), self.coords_to_mask(coords))));
+        // The order only depends on the board, not the dictionary: by start cell, then word, then the rest of the path.
+        // The cover search tries candidates in this order, so it also decides which tiling is found first.
+        found.sort_by(|(word, coords), (other_word, other_coords)| {
+            (coords.first(), word, coords).cmp(&(other_coords.first(), other_word, other_coords))
+        });
+        if let Some(seed) = budget.seed {
+            shuffle(&mut found, seed);
+        }
+        found
+    }
+
+    fn words_from(&self, trie: &Trie, start: (usize, usize), budget: &Budget) -> (Vec<Candidate>, CellSet) {
+        // the words starting at `start`, and every cell the walk looked at on the way
+        let mut visited_tracker: Vec<Vec<bool>> = vec![vec![false; self.grid.cols()]; self.grid.rows()];
+        let mut reached = CellSet::empty(self.num_cells());
+        let mut guess_word = String::new();
+        let mut guess_coords = vec![];
+        let mut found = vec![];
+        self.recurse_find_words(
+            trie,
+            (start.0 as isize, start.1 as isize),
+            &mut visited_tracker,
+            &mut reached,
+            &mut guess_word,
+            &mut guess_coords,
+            &mut found,
+            budget,
+        );
+        (found, reached)
+    }
+
+    pub fn solve(&self, trie: &Trie) -> Option<Solution> {
+        self.solve_with_stats(trie).0
+    }
+
+    pub fn solve_with_stats(&self, trie: &Trie) -> (Option<Solution>, SearchStats) {
+        self.solve_within(trie, &Budget::unlimited())
+    }
+
+    fn solve_within(&self, trie: &Trie, budget: &Arc<Budget>) -> (Option<Solution>, SearchStats) {
+        let mut stats = SearchStats::default();
+        budget.observe(|observer| observer.phase(Phase::FindingWords));
+        let candidates = self.find_candidates_within(trie, budget);
+        if budget.stop_reason().is_some() {
+            return (None, stats);
+        }
+        budget.observe(|observer| observer.candidates_found(&self.placements(&candidates)));
+
+        budget.observe(|observer| observer.phase(Phase::Covering));
+        let result = self.search_solutions(&candidates, &[], 1, budget, &mut stats).pop();
+        if let Some(solution) = &result {
+            budget.observe(|observer| observer.solution_found(solution));
+        }
+        budget.observe(|observer| observer.finished(&stats));
+        (result, stats)
+    }
+
+    pub fn solve_all(&self, trie: &Trie) -> Vec<Solution> {
+        // Every distinct tiling of the board. Each tiling is reported once, regardless of word order.
+        let candidates = self.candidates(trie);
+        self.search_solutions(&candidates, &[], usize::MAX, &Budget::unlimited(), &mut SearchStats::default())
+    }
+
+    pub fn count_solutions(&self, trie: &Trie, limit: usize) -> usize {
+        // Number of distinct tilings, stopping once `limit` is reached.
+        // `count_solutions(trie, 2) > 1` is the cheap way to ask if a board is ambiguous.
+        let candidates = self.candidates(trie);
+        self.search_solutions(&candidates, &[], limit, &Budget::unlimited(), &mut SearchStats::default()).len()
+    }
+
+    fn solution_search(&self, masks: Vec<CellSet>, limit: usize, budget: Arc<Budget>) -> SolutionSearch {
+        let rows = self.grid.rows();
+        let cols = self.grid.cols();
+        let mut by_cell = vec![vec![]; self.num_cells()];
+        for (index, mask) in masks.iter().enumerate() {
+            for cell in mask.iter() {
+                by_cell[cell].push(index);
+            }
+        }
+        // blocked cells are left out, so region flood fills never cross them
+        let mut neighbours = vec![vec![]; self.num_cells()];
+        for row in 0..rows as isize {
+            for col in 0..cols as isize {
+                for (next_row, next_col) in self.adjacency.cell_neighbours((row, col), rows, cols) {
+                    if !self.blocked((next_row, next_col)) {
+                        neighbours[row as usize * cols + col as usize].push(next_row as usize * cols + next_col as usize);
                     }
                 }
             }
         }
-        println!("Found {:?} potential words.", candidates.keys().len());
-        let mut word_list = Vec::new();
-        for word in candidates.keys() {
-            word_list.push(word.clone());
+        SolutionSearch {
+            min_word_len: masks.iter().map(|mask| mask.count()).min().unwrap_or(0),
+            masks,
+            crossings: vec![],
+            by_cell,
+            neighbours,
+            full: self.open_mask(),
+            num_cells: self.num_cells(),
+            num_blocks: self.num_blocks(),
+            bounds: self.answer_bounds(),
+            limit,
+            budget,
         }
-        word_list.sort_by(|x, y| y.len().cmp(&x.len()));
-        println!("Words: ");
-        for word in word_list {
-            println!("{:?}", word);
+    }
+
+    fn candidate_search<'a>(&self, candidates: impl IntoIterator<Item = &'a Candidate>, limit: usize, budget: Arc<Budget>) -> SolutionSearch {
+        // every candidate's coverage is computed once up front, the search only does mask operations
+        let (masks, crossings) = candidates
+            .into_iter()
+            .map(|(_, coords)| (self.coords_to_mask(coords), self.crossing_mask(coords)))
+            .unzip();
+        SolutionSearch { crossings, ..self.solution_search(masks, limit, budget) }
+    }
+
+    fn placements(&self, candidates: &[Candidate]) -> Vec<Placement> {
+        candidates.iter().map(|candidate| self.placement(candidate)).collect()
+    }
+
+    fn search_solutions(&self, candidates: &[Candidate], locked: &[Candidate], limit: usize, budget: &Arc<Budget>, stats: &mut SearchStats) -> Vec<Solution> {
+        // `locked` placements are part of every solution, the search only fills in the cells they leave uncovered.
+        // They're numbered after the candidates.
+        // If `budget` runs out, the solutions found until then are returned.
+        let placements: Vec<&Candidate> = candidates.iter().chain(locked).collect();
+        let search = self.candidate_search(placements.iter().copied(), limit, budget.clone());
+        let start: Vec<usize> = (candidates.len()..placements.len()).collect();
+        let found = Self::run_search(&search, &start, stats);
+        let to_solution = |indices: Vec<usize>| {
+            let chosen: Vec<&Candidate> = indices.into_iter().map(|index| placements[index]).collect();
+            self.solution(&chosen)
+        };
+        if budget.stop_reason().is_some() {
+            budget.set_partial(to_solution(budget.best_partial()));
         }
+        found.into_iter().map(to_solution).collect()
+    }
 
-        let mut potential_solutions = vec![];
-        let depth = 1;
-        Self::recurse_find_solution(&self.puzzle, &mut potential_solutions, &candidates, self.num_answers, depth);
-        let mut result: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        for word in potential_solutions {
-            result.insert(word.clone(), candidates.get(&word).unwrap().to_vec());
+    fn run_search(search: &SolutionSearch, start: &[usize], stats: &mut SearchStats) -> Vec<Vec<usize>> {
+        // Searches for solutions containing the `start` placements, which must not overlap each other
+        let mut used = CellSet::empty(search.num_cells);
+        let mut crossed = CellSet::empty(search.num_blocks);
+        for &index in start {
+            // `solve_with_locked` already refuses locked answers that overlap or cross, this keeps the search sound without it
+            if used.overlaps(&search.masks[index]) || search.crossings.get(index).is_some_and(|blocks| blocks.overlaps(&crossed)) {
+                return vec![];
+            }
+            used.union_with(&search.masks[index]);
+            if let Some(blocks) = search.crossings.get(index) {
+                crossed.union_with(blocks);
+            }
         }
-        return Some(result);
+        if start.iter().any(|&index| Self::dead_region(search, &used, &search.masks[index], stats).is_some()) {
+            return vec![];
+        }
+        let mut found = vec![];
+        Self::recurse_find_solution(search, start, &used, &mut found, stats);
+        found
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn branch_search(search: &SolutionSearch, mut cursor: Cursor, stats: &mut SearchStats) -> Vec<Vec<usize>> {
+        let mut found = vec![];
+        while found.len() < search.limit {
+            let Some(solution) = cursor.next(search, &mut ()) else {
+                break;
+            };
+            found.push(solution.to_vec());
+        }
+        *stats += cursor.stats;
+        found
     }
 
-    fn recurse_find_words(puzzle: &Vec<Vec<char>>, trie: &Trie, start: (isize, isize), visited: &mut Vec<Vec<bool>>, guess_word: &mut String, guess_coords: &mut Vec<(isize, isize)>, result: &mut HashMap<String, Vec<(isize, isize)>>) {
+    #[cfg(feature = "parallel")]
+    fn branch_search(search: &SolutionSearch, cursor: Cursor, stats: &mut SearchStats) -> Vec<Vec<usize>> {
+        // Each top level branch (a candidate covering the first open cell) is searched on its own.
+        // Branch results are joined in the same order the sequential search visits them, then cut to the limit,
+        // so the solutions match the sequential path exactly. Once the branches up to some point have found
+        // `search.limit` solutions between them, the ones after it are cancelled, since they'd be cut anyway.
+        let branches = cursor.split();
+        let found_per_branch: Vec<AtomicUsize> = branches.iter().map(|_| AtomicUsize::new(0)).collect();
+        let enough = AtomicUsize::new(usize::MAX);
+        let branches: Vec<(Vec<Vec<usize>>, SearchStats)> = branches
+            .into_par_iter()
+            .enumerate()
+            .map(|(branch, mut cursor)| {
+                let mut hook = BranchHook { branch, enough: &enough };
+                let mut found = vec![];
+                while found.len() < search.limit {
+                    let Some(solution) = cursor.next(search, &mut hook) else {
+                        break;
+                    };
+                    found.push(solution.to_vec());
+                    found_per_branch[branch].fetch_add(1, Ordering::Relaxed);
+                    let mut total = 0;
+                    for (earlier, count) in found_per_branch.iter().enumerate() {
+                        total += count.load(Ordering::Relaxed);
+                        if total >= search.limit {
+                            enough.fetch_min(earlier, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                }
+                (found, cursor.stats)
+            })
+            .collect();
+        let mut found = vec![];
+        for (branch_found, branch_stats) in branches {
+            *stats += branch_stats;
+            found.extend(branch_found);
+        }
+        found.truncate(search.limit);
+        found
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn recurse_find_words(&self, trie: &Trie, start: (isize, isize), visited: &mut Vec<Vec<bool>>, reached: &mut CellSet, guess_word: &mut String, guess_coords: &mut Vec<(isize, isize)>, result: &mut Vec<Candidate>, budget: &Budget) {
         // Given a coordinate `start`, will find all possible words starting at that point by recursively checking adjacent letters
+        let grid = &self.grid;
 
         //Grid boundary checks
         let (row, col) = start;
-        if row < 0 || col < 0 || row >= puzzle.len() as isize || col >= puzzle[0].len() as isize {
+        if row < 0 || col < 0 || row >= grid.rows() as isize || col >= grid.cols() as isize {
             // the comparisons to 0 are partly why the starting coords need to be an isize
             return;
         }
         if visited[row as usize][col as usize] {
             return;
         }
+        // what's found from here depends on this cell's tile, see `Session::set_tile`
+        reached.insert(grid.index(Coord::from_cell(start)));
+        if self.blocked(start) || !budget.expand() {
+            return;
+        }
 
-        //Append current letter to the guess
-        guess_word.push(puzzle[row as usize][col as usize]);
+        //Append current tile to the guess, all of its letters at once
+        let guess_len = guess_word.len();
+        guess_word.push_str(&grid[Coord::from_cell(start)]);
+        // nothing longer is allowed, so no point walking any further
+        if self.rules.max_word_len.is_some_and(|max| guess_word.chars().count() > max) {
+            guess_word.truncate(guess_len);
+            return;
+        }
         guess_coords.push((row, col));
         visited[row as usize][col as usize] = true;
 
         //If guess is valid (in dict and is word end), add to result
         if let Some(word) = trie.search(guess_word){
-            if word.1 {
-                result.insert(guess_word.clone(), guess_coords.clone());
+            if word.1 && self.rules.allows_len(guess_word.chars().count()) {
+                result.push((guess_word.clone(), guess_coords.clone()));
             }
-            let directions: [(isize, isize); 8] = [
-                (-1, 0), (-1, 1), (0, 1), (1, 1),
-                (1, 0), (1, -1), (0, -1), (-1, -1)
-            ];
-            for &(dir_row, dir_col) in directions.iter() {
-                Self::recurse_find_words(
-                    puzzle,
+            for next in self.adjacency.cell_neighbours(start, grid.rows(), grid.cols()) {
+                self.recurse_find_words(
                     trie,
-                    (start.0 + dir_row, start.1 + dir_col),
+                    next,
                     visited,
+                    reached,
                     guess_word,
                     guess_coords,
-                    result
+                    result,
+                    budget,
                 );
             }
         }
         visited[row as usize][col as usize] = false;
-        guess_word.pop();
+        guess_word.truncate(guess_len);
         guess_coords.pop();
     }
 
-    fn recurse_find_solution(puzzle: &Vec<Vec<char>>, solution: &mut Vec<String>, candidates: &HashMap<String, Vec<(isize, isize)>>, num_answers: usize, mut depth: usize) {
-        // Given a list of candidates, will find the one that uses all possible words with no overlap (the solution).
-        if depth == num_answers {
-            //check to guarantee full coverage of the puzzle
-            let mut used_coords = vec![
-                vec![
-                    false; puzzle[0].len() as usize
-                ]; puzzle.len() as usize
-            ];
-            for word in &mut *solution { 
-                for coord in candidates.get(word).unwrap() {
-                    used_coords[coord.0 as usize][coord.1 as usize] = true;
+    fn recurse_find_solution(search: &SolutionSearch, solution: &[usize], used: &CellSet, found: &mut Vec<Vec<usize>>, stats: &mut SearchStats) {
+        // Given the cell masks of all candidates, will find the sets of candidates that cover every cell with no overlap (the solutions).
+        // `solution` holds candidate indices and `used` the union of their cell masks.
+        // Each solution is pushed to `found`, stopping once `search.limit` solutions are found.
+        // No longer recursive, the stepping happens in `Cursor`.
+        let cursor = Cursor::new(search, solution.to_vec(), used.clone(), &mut ());
+        found.extend(Self::branch_search(search, cursor, stats));
+    }
+
+    fn dead_region(search: &SolutionSearch, used: &CellSet, placed: &CellSet, stats: &mut SearchStats) -> Option<Refusal> {
+        // After placing `placed`, checks the connected regions of uncovered cells next to it.
+        // A region that is smaller than every candidate, or that no unused candidate fits inside, can never be covered,
+        // and which of the two it was is returned.
+        // Regions away from `placed` were already checked when they last changed.
+        let mut checked = used.clone();
+        for cell in placed.iter() {
+            for &start in &search.neighbours[cell] {
+                if checked.contains(start) {
+                    continue;
                 }
-            }
-            let mut all_used = true;
-            for row in used_coords {
-                for col in row {
-                    all_used &= col;
+                let region = Self::region_containing(search, used, start);
+                checked.union_with(&region);
+                if region.count() < search.min_word_len {
+                    stats.small_regions += 1;
+                    return Some(Refusal::SmallRegion);
+                }
+                if !search.masks.iter().any(|mask| mask.is_subset(&region)) {
+                    stats.unfillable_regions += 1;
+                    return Some(Refusal::UnfillableRegion);
                 }
             }
-            if all_used {
-                //answer found, it's stored in `solution`
-                return;
-            } 
         }
-        //haven't hit maximum depth yet. still potential solutions.
-        let mut potential_next = candidates.clone();
-        for word in &mut *solution {
-            potential_next.remove(word);
-        } //`potential_next` and `solution` now have no equal elements
-        for word1 in potential_next.keys() {
-            let mut overlap_found = false;
-            for word2 in &mut *solution {
-                if Self::has_overlap(candidates, word1, word2) {
-                    overlap_found = true;
+        None
+    }
+
+    fn region_containing(search: &SolutionSearch, used: &CellSet, start: usize) -> CellSet {
+        // flood fill over uncovered cells
+        let mut region = CellSet::empty(search.num_cells);
+        region.insert(start);
+        let mut frontier = vec![start];
+        while let Some(cell) = frontier.pop() {
+            for &next in &search.neighbours[cell] {
+                if !used.contains(next) && !region.contains(next) {
+                    region.insert(next);
+                    frontier.push(next);
                 }
             }
-            if !overlap_found {
-                solution.push(word1.to_string());
-                depth += 1;
-                Self::recurse_find_solution(puzzle, solution, candidates, num_answers, depth);
-                solution.pop();
-                depth -= 1;
-            }
         }
+        region
     }
 
-    fn has_overlap(candidates: &HashMap<String, Vec<(isize, isize)>>, a: &String, b: &String) -> bool {
-        let a_coords: HashSet<_> = candidates.get(a).unwrap().iter().collect();
-        let b_coords: HashSet<_> = candidates.get(b).unwrap().iter().collect();
-        return !a_coords.is_disjoint(&b_coords);
-    }
+}
 
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    // Fisher-Yates driven by splitmix64. Not for anything that needs good randomness,
+    // just a reproducible order that's different for every seed.
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    for index in (1..items.len()).rev() {
+        items.swap(index, (next() % (index as u64 + 1)) as usize);
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_cast)]
 mod tests {
     use std::collections::HashMap;
+    use crate::adjacency::Adjacency;
+    use crate::bitboard::CellSet;
+    use crate::model::{Coord, Grid, GridError, Path, Solution};
     use crate::trie::Trie;
-    use super::Strands;
+    use super::{Budget, Exclusions, Rules, SearchStats, Strands};
+
+    pub(crate) fn cat_dog_bee(answers: impl Into<Option<usize>>, extra: &[&str]) -> (Strands, Trie) {
+        //CAT
+        //DOG
+        //BEE
+        // the rows tile the board, `extra` words can give it other tilings. Three letter words, so any length is allowed
+        let strands = Strands::new("CAT\nDOG\nBEE".to_string(), answers).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        for word in ["CAT", "DOG", "BEE"].iter().chain(extra) {
+            trie.insert(word.to_string());
+        }
+        (strands, trie)
+    }
+
+    fn answer_map(solution: &Solution) -> HashMap<String, Vec<(isize, isize)>> {
+        solution.placements.iter().map(|placement| (placement.word.clone(), placement.path.cells())).collect()
+    }
 
     #[test]
     fn test_strands_new() {
         let inputstr = "AB\nCD";
         let strands = Strands::new(inputstr.to_string(), 1);
         let good = Strands {
-            puzzle: vec![
-                vec!['A', 'B'],
-                vec!['C', 'D']
-            ],
-            num_answers: 1,
+            grid: Grid::parse("AB\nCD"),
+            num_answers: Some(1),
+            answer_range: None,
+            excluded: Exclusions::default(),
+            clue: None,
+            adjacency: Adjacency::Eight,
+            rules: Rules::default(),
         };
         assert_eq!(strands, good);
     }
 
     #[test]
     fn test_recurse_find_words_1() {
-        let strands = Strands::new("C".to_string(), 1);
+        let strands = Strands::new("C".to_string(), 1).with_rules(Rules::none());
         let mut trie = Trie::new();
         trie.insert("C".to_string());
 
-        let good: HashMap<String, Vec<(isize, isize)>> = HashMap::from([
+        let good: Vec<(String, Vec<(isize, isize)>)> = vec![
             ("C".to_string(), vec![(0 as isize, 0 as isize)])
-        ]);
+        ];
         let mut visited = vec![vec![false]];
         let mut guess_word = String::new();
         let guess_coords = &mut vec![];
-        let mut result: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        Strands::recurse_find_words(&strands.puzzle, &trie, (0, 0), &mut visited, &mut guess_word, guess_coords, &mut result);
+        let mut result: Vec<(String, Vec<(isize, isize)>)> = Vec::new();
+        strands.recurse_find_words(&trie, (0, 0), &mut visited, &mut CellSet::empty(strands.num_cells()), &mut guess_word, guess_coords, &mut result, &Budget::unlimited());
         assert_eq!(result, good)
     }
 
     #[test]
     fn test_recurse_find_words_2() {
-        let strands = Strands::new("CA".to_string(), 1);
+        let strands = Strands::new("CA".to_string(), 1).with_rules(Rules::none());
         let mut trie = Trie::new();
         trie.insert("CA".to_string());
-        let good: HashMap<String, Vec<(isize, isize)>> = HashMap::from([
+        let good: Vec<(String, Vec<(isize, isize)>)> = vec![
             ("CA".to_string(), vec![(0 as isize, 0 as isize), (0 as isize, 1 as isize)])
-        ]);
+        ];
         let mut visited = vec![vec![false; 2]];
         let mut guess_words = String::new();
         let guess_coords = &mut vec![];
-        let mut result: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        Strands::recurse_find_words(&strands.puzzle, &trie, (0, 0), &mut visited, &mut guess_words, guess_coords, &mut result);
+        let mut result: Vec<(String, Vec<(isize, isize)>)> = Vec::new();
+        strands.recurse_find_words(&trie, (0, 0), &mut visited, &mut CellSet::empty(strands.num_cells()), &mut guess_words, guess_coords, &mut result, &Budget::unlimited());
 
         assert_eq!(result, good) 
     }
 
     #[test]
     fn test_recurse_find_words_3() {
-        let strands = Strands::new("CA\nTS".to_string(), 1);
+        let strands = Strands::new("CA\nTS".to_string(), 1).with_rules(Rules::none());
         let mut trie = Trie::new();
         trie.insert("CAT".to_string());
         trie.insert("CATS".to_string());
-        let mut good: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        good.insert("CAT".to_string(), vec![
-            (0 as isize, 0 as isize), (0 as isize, 1 as isize),
-            (1 as isize, 0 as isize)]);
-        good.insert("CATS".to_string(), vec![
-            (0 as isize, 0 as isize), (0 as isize, 1 as isize),
-            (1 as isize, 0 as isize), (1 as isize, 1 as isize)]);
+        let good: Vec<(String, Vec<(isize, isize)>)> = vec![
+            ("CAT".to_string(), vec![
+                (0 as isize, 0 as isize), (0 as isize, 1 as isize),
+                (1 as isize, 0 as isize)]),
+            ("CATS".to_string(), vec![
+                (0 as isize, 0 as isize), (0 as isize, 1 as isize),
+                (1 as isize, 0 as isize), (1 as isize, 1 as isize)]),
+        ];
         let mut visited = vec![vec![false; 2]; 2];
         let mut guess_words = String::new();
         let guess_coords = &mut vec![];
-        let mut result: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        Strands::recurse_find_words(&strands.puzzle, &trie, (0, 0), &mut visited, &mut guess_words, guess_coords, &mut result);
+        let mut result: Vec<(String, Vec<(isize, isize)>)> = Vec::new();
+        strands.recurse_find_words(&trie, (0, 0), &mut visited, &mut CellSet::empty(strands.num_cells()), &mut guess_words, guess_coords, &mut result, &Budget::unlimited());
         assert_eq!(result, good)
     }
 
     #[test]
     fn test_recurse_find_words_4() {
-        let strands = Strands::new("CA\nTD".to_string(), 1);
+        let strands = Strands::new("CA\nTD".to_string(), 1).with_rules(Rules::none());
         let mut trie = Trie::new();
         trie.insert("CAT".to_string());
-        let mut good: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        good.insert("CAT".to_string(), vec![
-            (0 as isize, 0 as isize), (0 as isize, 1 as isize),
-            (1 as isize, 0 as isize)]);
+        let good: Vec<(String, Vec<(isize, isize)>)> = vec![
+            ("CAT".to_string(), vec![
+                (0 as isize, 0 as isize), (0 as isize, 1 as isize),
+                (1 as isize, 0 as isize)]),
+        ];
         let mut visited = vec![vec![false; 2]; 2];
         let mut guess_words = String::new();
         let guess_coords = &mut vec![];
-        let mut result: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        Strands::recurse_find_words(&strands.puzzle, &trie, (0, 0), &mut visited, &mut guess_words, guess_coords, &mut result);
+        let mut result: Vec<(String, Vec<(isize, isize)>)> = Vec::new();
+        strands.recurse_find_words(&trie, (0, 0), &mut visited, &mut CellSet::empty(strands.num_cells()), &mut guess_words, guess_coords, &mut result, &Budget::unlimited());
         assert_eq!(result, good)
     }
 
     #[test]
     fn test_find_overlap_1() {
-        let mut candidates: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        candidates.insert("A".to_string(), vec![(0,0)]);
-        let a = "A".to_string();
-        let b = "A".to_string();
-        assert!(Strands::has_overlap(&candidates, &a, &b))
+        let strands = Strands::new("A".to_string(), 1);
+        let a = strands.coords_to_mask(&[(0,0)]);
+        let b = strands.coords_to_mask(&[(0,0)]);
+        assert!(a.overlaps(&b))
     }
 
     #[test]
     fn test_find_overlap_2() {
-        let mut candidates: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        candidates.insert("ABC".to_string(), vec![(0,0),(0,1),(1,0)]);
-        candidates.insert("BCD".to_string(), vec![(0,1),(1,0),(1,1)]);
-        let a = "ABC".to_string();
-        let b = "BCD".to_string();
-        assert!(Strands::has_overlap(&candidates, &a, &b))
+        let strands = Strands::new("AB\nCD".to_string(), 2);
+        let a = strands.coords_to_mask(&[(0,0),(0,1),(1,0)]);
+        let b = strands.coords_to_mask(&[(0,1),(1,0),(1,1)]);
+        assert!(a.overlaps(&b))
     }
 
     #[test]
     fn test_find_overlap_3() {
-        let mut candidates: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        candidates.insert("ABCDEFG".to_string(), vec![(0,0),(0,1),(0,2),(1,0),(1,1),(1,2),(2,0),(2,1),(2,2)]);
-        candidates.insert("D".to_string(), vec![(1,1)]);
-        let a = "ABCDEFG".to_string();
-        let b = "D".to_string();
-        assert!(Strands::has_overlap(&candidates, &a, &b))
+        let strands = Strands::new("ABC\nDEF\nGHI".to_string(), 2);
+        let a = strands.coords_to_mask(&[(0,0),(0,1),(0,2),(1,0),(1,1),(1,2),(2,0),(2,1),(2,2)]);
+        let b = strands.coords_to_mask(&[(1,1)]);
+        assert!(a.overlaps(&b))
+    }
+
+    #[test]
+    fn test_find_overlap_4() {
+        let strands = Strands::new("AB\nCD".to_string(), 2);
+        let a = strands.coords_to_mask(&[(0,0),(0,1)]);
+        let b = strands.coords_to_mask(&[(1,0),(1,1)]);
+        assert!(!a.overlaps(&b))
     }
 
     #[test]
     fn test_recurse_find_solution_1() {
-        let good = vec!["ABCD".to_string()];
+        let good = vec![vec![0]];
         
         let strands = Strands::new("AB\nCD".to_string(),1);
-        let mut solution = Vec::new();
-        let mut candidates: HashMap<String, Vec<(isize, isize)>> = HashMap::new();
-        candidates.insert("ABCD".to_string(), vec![(0,0),(0,1),(1,0),(1,1)]);
-        let depth = 1;
-        Strands::recurse_find_solution(&strands.puzzle, &mut solution, &candidates, strands.num_answers, depth);
+        let solution = Vec::new();
+        let masks = vec![strands.coords_to_mask(&[(0,0),(0,1),(1,0),(1,1)])];
+        let budget = Budget::unlimited();
+        let search = strands.solution_search(masks, 1, budget);
+        let used = CellSet::empty(4);
+        let mut found = Vec::new();
+        Strands::recurse_find_solution(&search, &solution, &used, &mut found, &mut SearchStats::default());
         
-        assert_eq!(solution, good);
+        assert_eq!(found, good);
+    }
+
+    #[test]
+    fn test_recurse_find_solution_2() {
+        // the only words leave a cell uncovered, so there is no solution
+        let strands = Strands::new("AB\nCD".to_string(),1);
+        let solution = Vec::new();
+        let masks = vec![strands.coords_to_mask(&[(0,0),(0,1),(1,0)])];
+        let budget = Budget::unlimited();
+        let search = strands.solution_search(masks, 1, budget);
+        let used = CellSet::empty(4);
+        let mut found = Vec::new();
+        Strands::recurse_find_solution(&search, &solution, &used, &mut found, &mut SearchStats::default());
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_recurse_find_solution_3() {
+        // two tilings of a 2x2 board (rows or columns), each found once even though word order could vary
+        let strands = Strands::new("AB\nCD".to_string(),2);
+        let solution = Vec::new();
+        let masks = vec![
+            strands.coords_to_mask(&[(0,0),(0,1)]),
+            strands.coords_to_mask(&[(1,0),(1,1)]),
+            strands.coords_to_mask(&[(0,0),(1,0)]),
+            strands.coords_to_mask(&[(0,1),(1,1)]),
+        ];
+        let budget = Budget::unlimited();
+        let search = strands.solution_search(masks, usize::MAX, budget);
+        let used = CellSet::empty(4);
+        let mut found = Vec::new();
+        Strands::recurse_find_solution(&search, &solution, &used, &mut found, &mut SearchStats::default());
+        assert_eq!(found, vec![vec![0, 1], vec![2, 3]]);
     }
 
     #[test]
     fn test_solve_1() {
-        let strands = Strands::new("CAT\nDOG\nBEE".to_string(), 3);
+        let strands = Strands::new("CAT\nDOG\nBEE".to_string(), 3).with_rules(Rules::none());
         // CAT
         // DOG
         // BEE
@@ -339,7 +800,7 @@ mod tests {
             (2 as isize, 0 as isize), (2 as isize, 1 as isize),
             (2 as isize, 2 as isize)]);
         let result = strands.solve(&trie).unwrap();
-        assert_eq!(result, good)
+        assert_eq!(answer_map(&result), good)
     }
 
     #[test]
@@ -357,7 +818,7 @@ mod tests {
             (1 as isize, 1 as isize), (2 as isize, 1 as isize), (2 as isize, 2 as isize), 
             (1 as isize, 2 as isize), (0 as isize, 2 as isize), (0 as isize, 1 as isize),
             (0 as isize, 0 as isize), (1 as isize, 0 as isize), (2 as isize, 0 as isize)]);
-        assert_eq!(strands.solve(&trie), Some(good));
+        assert_eq!(strands.solve(&trie).as_ref().map(answer_map), Some(good));
     }
 
     #[test]
@@ -365,7 +826,7 @@ mod tests {
         //CAT
         //DOG
         //EES
-        let strands = Strands::new("CAT\nDOG\nEES".to_string(), 3);
+        let strands = Strands::new("CAT\nDOG\nEES".to_string(), 3).with_rules(Rules::none());
         let mut trie = Trie::new();
         trie.insert("CAT".to_string());
         trie.insert("DOG".to_string());
@@ -382,6 +843,331 @@ mod tests {
             (2 as isize, 2 as isize), (2 as isize, 1 as isize),
             (2 as isize, 0 as isize)]);
         let result = strands.solve(&trie).unwrap();
-        assert_eq!(result, good)
+        assert_eq!(answer_map(&result), good)
+    }
+    #[test]
+    fn test_solve_unknown_answers() {
+        //CAT
+        //DOG
+        //BEE
+        let (strands, trie) = cat_dog_bee(None, &[]);
+        let result = strands.solve(&trie).unwrap();
+        assert_eq!(result.len(), 3);
+        assert!(result.contains_word("CAT") && result.contains_word("DOG") && result.contains_word("BEE"));
+    }
+
+    #[test]
+    fn test_solve_answer_range() {
+        //CAT
+        //DOG
+        //BEE
+        // the whole board is also one snaking word, so the range decides which tiling is allowed
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+
+        let result = strands.clone().with_answer_range(2, 4).solve(&trie).unwrap();
+        assert_eq!(result.len(), 3);
+
+        let result = strands.clone().with_answer_range(1, 1).solve(&trie).unwrap();
+        assert!(result.contains_word("CATGODBEE"));
+
+        assert_eq!(strands.with_answer_range(4, 9).solve(&trie), None);
+
+        // a range replaces an exact count
+        let (strands, trie) = cat_dog_bee(3, &["CATGODBEE"]);
+        let strands = strands.with_answer_range(1, 1);
+        assert_eq!(strands.num_answers, None);
+        assert!(strands.solve(&trie).unwrap().contains_word("CATGODBEE"));
+    }
+
+    #[test]
+    #[should_panic(expected = "above the maximum")]
+    fn test_answer_range_reversed() {
+        Strands::new("CAT".to_string(), None).with_answer_range(3, 1);
+    }
+
+    #[test]
+    fn test_find_candidates_multiple_paths() {
+        //EYE
+        //XXX
+        // "EYE" only has one set of cells, read either way. "EX" can start from either end.
+        let strands = Strands::new("EYE\nXXX".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("EYE".to_string());
+        trie.insert("EX".to_string());
+        let candidates = strands.find_candidates(&trie);
+        assert_eq!(candidates.iter().filter(|placement| placement.word == "EYE").count(), 1);
+        assert_eq!(candidates.iter().filter(|placement| placement.word == "EX").count(), 4);
+    }
+
+    #[test]
+    fn test_solve_all() {
+        //AB
+        //CD
+        let strands = Strands::new("AB\nCD".to_string(), 2).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("AB".to_string());
+        trie.insert("CD".to_string());
+        trie.insert("AC".to_string());
+        trie.insert("BD".to_string());
+        trie.insert("AD".to_string());
+        let solutions = strands.solve_all(&trie);
+        assert_eq!(solutions.len(), 2);
+        let mut words: Vec<Vec<String>> = solutions
+            .iter()
+            .map(|solution| {
+                let mut words: Vec<String> = solution.words().cloned().collect();
+                words.sort();
+                words
+            })
+            .collect();
+        words.sort();
+        assert_eq!(words, vec![vec!["AB".to_string(), "CD".to_string()], vec!["AC".to_string(), "BD".to_string()]]);
+    }
+
+    #[test]
+    fn test_candidate_order() {
+        //AB
+        //CD
+        // sorted by start cell, then word, whatever order the dictionary lists them in
+        let words = ["DC", "BA", "AD", "AB", "CA"];
+        let strands = Strands::new("AB\nCD".to_string(), 2).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        for word in words {
+            trie.insert(word.to_string());
+        }
+        let mut reversed = Trie::new();
+        for word in words.iter().rev() {
+            reversed.insert(word.to_string());
+        }
+        let candidates: Vec<String> = strands.find_candidates(&trie).into_iter().map(|placement| placement.word).collect();
+        assert_eq!(candidates, vec!["AB", "AD", "BA", "CA", "DC"]);
+        assert_eq!(strands.find_candidates(&reversed), strands.find_candidates(&trie));
+        assert_eq!(strands.solve_all(&reversed), strands.solve_all(&trie));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_count_solutions() {
+        //CAT
+        //DOG
+        //BEE
+        let (strands, mut trie) = cat_dog_bee(None, &[]);
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+
+        trie.insert("CATGODBEE".to_string());
+        assert_eq!(strands.count_solutions(&trie, 10), 2);
+        assert_eq!(strands.count_solutions(&trie, 1), 1);
+    }
+
+    #[test]
+    fn test_prune_small_region() {
+        // ABCDE
+        // "ABCD" is tried first for the first cell, but leaves "E" on its own
+        let strands = Strands::new("ABCDE".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("ABCD".to_string());
+        trie.insert("BA".to_string());
+        trie.insert("CDE".to_string());
+        let (result, stats) = strands.solve_with_stats(&trie);
+        let result = result.unwrap();
+        assert!(result.contains_word("BA") && result.contains_word("CDE"));
+        assert_eq!(stats.small_regions, 1);
+        assert_eq!(stats.unfillable_regions, 0);
+    }
+
+    #[test]
+    fn test_prune_unfillable_region() {
+        // ABCDEF
+        // "ABCD" leaves "EF", which is long enough for a word but isn't one
+        let strands = Strands::new("ABCDEF".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("ABCD".to_string());
+        trie.insert("BA".to_string());
+        trie.insert("CDEF".to_string());
+        let (result, stats) = strands.solve_with_stats(&trie);
+        let result = result.unwrap();
+        assert!(result.contains_word("BA") && result.contains_word("CDEF"));
+        assert_eq!(stats.small_regions, 0);
+        assert_eq!(stats.unfillable_regions, 1);
+        assert_eq!(stats.nodes, 3);
+    }
+
+    #[test]
+    fn test_find_candidates_four_way() {
+        //CA
+        //TS
+        // "CAT" needs a diagonal step, "CTS" doesn't
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        trie.insert("CTS".to_string());
+        let strands = Strands::new("CA\nTS".to_string(), None).with_rules(Rules::none());
+        assert_eq!(strands.find_candidates(&trie).len(), 2);
+        let strands = strands.with_adjacency(Adjacency::Four);
+        let candidates = strands.candidates(&trie);
+        assert_eq!(candidates, vec![("CTS".to_string(), vec![(0, 0), (1, 0), (1, 1)])]);
+    }
+
+    #[test]
+    fn test_find_candidates_torus() {
+        //ABC
+        // "CA" wraps around the right edge
+        let mut trie = Trie::new();
+        trie.insert("CA".to_string());
+        let strands = Strands::new("ABC".to_string(), None).with_rules(Rules::none());
+        assert!(strands.find_candidates(&trie).is_empty());
+        let strands = strands.with_adjacency(Adjacency::Torus);
+        assert_eq!(strands.candidates(&trie), vec![("CA".to_string(), vec![(0, 2), (0, 0)])]);
+    }
+
+    #[test]
+    fn test_solve_hex() {
+        //AB
+        //CD
+        // the odd row is shifted half a cell right, so B touches C but A doesn't touch D
+        let mut trie = Trie::new();
+        trie.insert("AD".to_string());
+        trie.insert("BC".to_string());
+        trie.insert("AB".to_string());
+        trie.insert("CD".to_string());
+        let strands = Strands::new("AB\nCD".to_string(), 2).with_rules(Rules::none()).with_adjacency(Adjacency::Hex);
+        let candidates: Vec<String> = strands.find_candidates(&trie).into_iter().map(|placement| placement.word).collect();
+        assert_eq!(candidates, vec!["AB".to_string(), "BC".to_string(), "CD".to_string()]);
+        let result = strands.solve(&trie).unwrap();
+        assert!(result.contains_word("AB") && result.contains_word("CD"));
+    }
+
+    #[test]
+    fn test_is_spangram() {
+        //ABC
+        //DEF
+        //GHI
+        let strands = Strands::new("ABC\nDEF\nGHI".to_string(), None);
+        assert!(strands.is_spangram(&Path::from(vec![(0, 0), (1, 1), (2, 1)])));
+        assert!(strands.is_spangram(&Path::from(vec![(1, 0), (1, 1), (1, 2)])));
+        assert!(!strands.is_spangram(&Path::from(vec![(0, 0), (0, 1), (1, 1)])));
+        let abc = ("ABC".to_string(), vec![(0, 0), (0, 1), (0, 2)]);
+        let def = ("DEF".to_string(), vec![(1, 0), (1, 1), (1, 2)]);
+        let ghe = ("GHE".to_string(), vec![(2, 0), (2, 1), (1, 1)]);
+        let solution = strands.solution(&[&def, &ghe, &abc]);
+        let spangrams: Vec<&str> = solution.spangrams().iter().map(|placement| placement.word.as_str()).collect();
+        assert_eq!(spangrams, vec!["ABC", "DEF"]);
+    }
+
+    #[test]
+    fn test_strands_new_ragged() {
+        let strands = Strands::new("ABC\nD E\nF".to_string(), None);
+        assert_eq!(strands.grid.tile_rows().collect::<Vec<_>>(), vec![
+            vec!["A", "B", "C"],
+            vec!["D", ".", "E"],
+            vec!["F", ".", "."],
+        ]);
+        assert!(strands.is_blocked(Coord::new(1, 1)));
+        assert!(!strands.is_blocked(Coord::new(1, 2)));
+    }
+
+    #[test]
+    fn test_blocked_not_traversed() {
+        //C.T
+        //.A.
+        // "CAT" goes around the blocked cells diagonally, "C.T" can't be read through them
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        trie.insert("C.T".to_string());
+        let strands = Strands::new("C.T\n.A.".to_string(), None).with_rules(Rules::none());
+        let candidates = strands.candidates(&trie);
+        assert_eq!(candidates, vec![("CAT".to_string(), vec![(0, 0), (1, 1), (0, 2)])]);
+    }
+
+    #[test]
+    fn test_solve_masked() {
+        //CAT.
+        //..DOG
+        // blocked cells and the short first row don't need covering
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        trie.insert("DOG".to_string());
+        let strands = Strands::new("CAT.\n..DOG".to_string(), None).with_rules(Rules::none());
+        let result = strands.solve(&trie).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["DOG"], vec![(1, 2), (1, 3), (1, 4)]);
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+    }
+
+    #[test]
+    fn test_solve_masked_regions() {
+        //AB.CD
+        // the blocked cell splits the board into two regions, each needs its own word
+        let mut trie = Trie::new();
+        trie.insert("AB".to_string());
+        trie.insert("CD".to_string());
+        trie.insert("ABCD".to_string());
+        let strands = Strands::new("AB.CD".to_string(), None).with_rules(Rules::none());
+        assert!(strands.find_candidates(&trie).iter().all(|placement| placement.word != "ABCD"));
+        let result = strands.solve(&trie).unwrap();
+        assert!(result.contains_word("AB") && result.contains_word("CD"));
+    }
+
+    #[test]
+    fn test_is_spangram_masked() {
+        //.AB
+        //.CD
+        // column 0 is blocked, so the left side is column 1
+        let strands = Strands::new(".AB\n.CD".to_string(), None);
+        assert!(strands.is_spangram(&Path::from(vec![(0, 1), (0, 2)])));
+        assert!(strands.is_spangram(&Path::from(vec![(0, 1), (1, 1)])));
+    }
+
+    #[test]
+    fn test_is_spangram_one_row() {
+        //ABCDEF
+        // top and bottom are the same row, so only left to right counts
+        let strands = Strands::new("ABCDEF".to_string(), None);
+        assert!(!strands.is_spangram(&Path::from(vec![(0, 0), (0, 1)])));
+        assert!(!strands.is_spangram(&Path::from(vec![(0, 2), (0, 3), (0, 4)])));
+        assert!(strands.is_spangram(&Path::from(vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5)])));
+        let column = Strands::new("A\nB\nC".to_string(), None);
+        assert!(!column.is_spangram(&Path::from(vec![(0, 0), (1, 0)])));
+        assert!(column.is_spangram(&Path::from(vec![(0, 0), (1, 0), (2, 0)])));
+    }
+
+    #[test]
+    fn test_strands_parse() {
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        trie.insert("DOG".to_string());
+        let strands = Strands::parse("cat\nDoG\n").unwrap().with_rules(Rules::none());
+        assert_eq!(strands, Strands::new("CAT\nDOG".to_string(), None).with_rules(Rules::none()));
+        assert!(strands.solve(&trie).unwrap().contains_word("DOG"));
+        assert_eq!(Strands::parse("\n"), Err(GridError::Empty));
+        assert_eq!(Strands::parse("CAT\nDO"), Err(GridError::RaggedRow { line: 2, len: 2, expected: 3 }));
+    }
+
+    #[test]
+    fn test_strands_new_tiles() {
+        let strands = Strands::new("[QU]IT\nA[ING]\n[B".to_string(), None);
+        assert_eq!(strands.grid.tile_rows().collect::<Vec<_>>(), vec![
+            vec!["QU", "I", "T"],
+            vec!["A", "ING", "."],
+            vec!["[", "B", "."],
+        ]);
+    }
+
+    #[test]
+    fn test_find_words_tiles() {
+        //[QU] I
+        // T   [ING]
+        // a tile is used whole, "QI" can't be read from the "QU" tile
+        let mut trie = Trie::new();
+        trie.insert("QUIT".to_string());
+        trie.insert("QUITING".to_string());
+        trie.insert("QI".to_string());
+        let strands = Strands::new("[QU]I\nT[ING]".to_string(), None);
+        let mut candidates = strands.candidates(&trie);
+        candidates.sort();
+        assert_eq!(candidates, vec![
+            ("QUIT".to_string(), vec![(0, 0), (0, 1), (1, 0)]),
+            ("QUITING".to_string(), vec![(0, 0), (0, 1), (1, 0), (1, 1)]),
+        ]);
+        let result = strands.solve(&trie).unwrap();
+        assert_eq!(result["QUITING"].len(), 4);
+    }
+}
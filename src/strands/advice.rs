@@ -0,0 +1,127 @@
+// Which word to try next in the game when the dictionary allows several tilings.
+// Each placement that shows up in some tiling is scored by how well submitting it splits the tilings:
+// the game either accepts it, leaving the tilings that use it, or doesn't, leaving the rest.
+
+use std::collections::HashMap;
+
+use super::{SolveOptions, Strands};
+use crate::model::{Path, Placement};
+use crate::trie::Trie;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct MoveScore {
+    pub placement: Placement,
+    pub tilings: usize, // how many of the tilings found use this placement
+    pub expected_remaining: f64, // tilings left after submitting it, averaged over both answers, every tiling taken as equally likely
+    pub information: f64, // bits learnt from submitting it, 0 when every tiling agrees on it
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Advice {
+    pub tilings: usize,
+    pub exhaustive: bool, // false if the limit or the options' budget cut the search short, so the counts are only a sample
+    pub moves: Vec<MoveScore>, // best first
+}
+
+impl Advice {
+    pub fn best(&self) -> Option<&MoveScore> {
+        self.moves.first()
+    }
+}
+
+impl Strands {
+    pub fn advise(&self, trie: &Trie, options: &SolveOptions, limit: usize) -> Advice {
+        // Looks at up to `limit` tilings and ranks every placement in them, most informative first.
+        // Ties go to the placement more tilings agree on, since it's likelier to be accepted, then to reading order.
+        let mut solutions = self.solutions(trie, options);
+        let mut counts: Vec<(Placement, usize)> = vec![];
+        let mut index: HashMap<(String, Path), usize> = HashMap::new();
+        let mut tilings = 0;
+        let mut exhaustive = true;
+        for solution in solutions.by_ref() {
+            if tilings == limit {
+                exhaustive = false;
+                break;
+            }
+            tilings += 1;
+            for placement in solution.placements {
+                let key = (placement.word.clone(), placement.path.clone());
+                match index.get(&key) {
+                    Some(&position) => counts[position].1 += 1,
+                    None => {
+                        index.insert(key, counts.len());
+                        counts.push((placement, 1));
+                    }
+                }
+            }
+        }
+        exhaustive &= solutions.stop_reason().is_none();
+
+        let total = tilings as f64;
+        let mut moves: Vec<MoveScore> = counts
+            .into_iter()
+            .map(|(placement, count)| {
+                let (accepted, rejected) = (count as f64, total - count as f64);
+                let entropy = |part: f64| if part == 0.0 { 0.0 } else { -(part / total) * (part / total).log2() };
+                MoveScore {
+                    placement,
+                    tilings: count,
+                    expected_remaining: (accepted * accepted + rejected * rejected) / total,
+                    information: entropy(accepted) + entropy(rejected),
+                }
+            })
+            .collect();
+        moves.sort_by(|a, b| {
+            b.information
+                .total_cmp(&a.information)
+                .then(b.tilings.cmp(&a.tilings))
+                .then_with(|| (&a.placement.path, &a.placement.word).cmp(&(&b.placement.path, &b.placement.word)))
+        });
+        Advice { tilings, exhaustive, moves }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::strands::SolveOptions;
+    use crate::strands::tests::cat_dog_bee;
+
+    #[test]
+    fn test_advise() {
+        // three tilings: the rows, "CAT" + "DOGEEB", and the whole board as one word
+        let (strands, trie) = cat_dog_bee(None, &["DOGEEB", "CATGODBEE"]);
+        let advice = strands.advise(&trie, &SolveOptions::new(), 10);
+        assert_eq!(advice.tilings, 3);
+        assert!(advice.exhaustive);
+        assert_eq!(advice.moves.len(), 5);
+        // every placement splits the tilings 1 to 2, "CAT" is the one most tilings agree on
+        let best = advice.best().unwrap();
+        assert_eq!(best.placement.word, "CAT");
+        assert_eq!(best.tilings, 2);
+        assert!((best.expected_remaining - 5.0 / 3.0).abs() < 1e-9);
+        assert!((best.information - 0.9183).abs() < 1e-4);
+        assert!(advice.moves.iter().all(|score| score.information == best.information));
+    }
+
+    #[test]
+    fn test_advise_unique() {
+        // one tiling, so every answer is certain and nothing is learnt
+        let (strands, trie) = cat_dog_bee(None, &["DOGEEB", "CATGODBEE"]);
+        let strands = strands.with_answer_range(3, 3);
+        let advice = strands.advise(&trie, &SolveOptions::new(), 10);
+        assert_eq!(advice.tilings, 1);
+        assert!(advice.moves.iter().all(|score| score.information == 0.0 && score.expected_remaining == 1.0));
+        assert_eq!(advice.best().unwrap().placement.word, "CAT");
+    }
+
+    #[test]
+    fn test_advise_limit() {
+        let (strands, trie) = cat_dog_bee(None, &["DOGEEB", "CATGODBEE"]);
+        let advice = strands.advise(&trie, &SolveOptions::new(), 2);
+        assert_eq!(advice.tilings, 2);
+        assert!(!advice.exhaustive);
+        let advice = strands.advise(&trie, &SolveOptions::new().with_max_nodes(3), 10);
+        assert_eq!((advice.tilings, advice.exhaustive), (0, false));
+        assert_eq!(advice.best(), None);
+    }
+}
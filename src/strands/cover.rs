@@ -0,0 +1,231 @@
+// The cover search as a state machine that pauses after every tiling.
+// `solve`, `solve_all`, the parallel branches, the lazy `Solutions` iterator and the explain tracer all step through
+// this one loop, so the rules for what may be placed (overlaps, dead regions, answer bounds) only live here.
+
+use super::{SearchStats, SolutionSearch, Strands};
+use crate::bitboard::CellSet;
+
+// Why a placement wasn't taken any further
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(super) enum Refusal {
+    Overlap, // shares a cell, or a diagonal block with `Rules::no_crossing`, with a placed answer
+    SmallRegion, // leaves a region smaller than the shortest candidate
+    UnfillableRegion, // leaves a region no unused candidate fits inside
+    TooFewAnswers, // covers the board with fewer answers than allowed
+    TooManyAnswers, // uses the last allowed answer with cells still open
+    Stopped, // the budget ran out, or the hook cancelled the search
+}
+
+// Told about every step of the search, for callers that want more than the tilings.
+// Everything defaults to doing nothing, `()` is the hook that ignores it all.
+pub(super) trait SearchHook {
+    // `index` was tried on top of `solution` and refused
+    fn refused(&mut self, _index: usize, _refusal: Refusal, _solution: &[usize]) {}
+    // `index` was placed, followed by `branch` for the next open cell or `solved`
+    fn placed(&mut self, _index: usize) {}
+    // started trying the candidates covering `cell`
+    fn branch(&mut self, _cell: usize) {}
+    // the placements so far tile the board
+    fn solved(&mut self) {}
+    // every candidate for the latest `branch` was tried, so the placement above it is taken back
+    fn exhausted(&mut self) {}
+    // checked at every node, true ends the search as if the budget ran out
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl SearchHook for () {}
+
+// One level of the search: the open cell being covered, and which of its candidates to try next
+#[derive(Clone)]
+struct Frame {
+    cell: usize,
+    next: usize, // position in `search.by_cell[cell]`
+    end: usize, // candidates from here on aren't tried at this level
+    placed: Option<usize>, // candidate currently placed at this level, taken back before trying the next one
+}
+
+#[derive(Clone)]
+pub(super) struct Cursor {
+    solution: Vec<usize>,
+    used: CellSet, // cells covered by `solution`
+    crossed: CellSet, // diagonal blocks stepped through by `solution`, with `no_crossing`
+    stack: Vec<Frame>,
+    pending: bool, // the starting state already tiles the board and hasn't been handed out yet
+    pub(super) stats: SearchStats,
+}
+
+impl Cursor {
+    pub(super) fn new(search: &SolutionSearch, solution: Vec<usize>, used: CellSet, hook: &mut impl SearchHook) -> Self {
+        // Starts below `solution`, which covers `used` and has already been checked for overlaps and dead regions
+        let mut crossed = CellSet::empty(search.num_blocks);
+        for blocks in solution.iter().filter_map(|&index| search.crossings.get(index)) {
+            crossed.union_with(blocks);
+        }
+        let mut cursor = Cursor { solution, used, crossed, stack: vec![], pending: false, stats: SearchStats::default() };
+        match cursor.enter(search, hook) {
+            Ok(None) => cursor.pending = true,
+            Ok(Some(cell)) => hook.branch(cell),
+            Err(_) => (),
+        }
+        cursor
+    }
+
+    #[cfg(feature = "parallel")]
+    pub(super) fn split(&self) -> Vec<Cursor> {
+        // One cursor per candidate at the current level, which together search exactly what this one would, in order.
+        // Meant for a fresh cursor, before `next` is first called.
+        let Some(frame) = self.stack.last() else {
+            return vec![self.clone()];
+        };
+        (frame.next..frame.end)
+            .map(|position| {
+                let mut cursor = self.clone();
+                let frame = cursor.stack.last_mut().expect("cloned with a frame");
+                frame.next = position;
+                frame.end = position + 1;
+                cursor
+            })
+            .collect()
+    }
+
+    pub(super) fn next(&mut self, search: &SolutionSearch, hook: &mut impl SearchHook) -> Option<&[usize]> {
+        // Steps the search on to its next tiling, as candidate indices. None once there are no more, or it had to stop.
+        if std::mem::take(&mut self.pending) {
+            return Some(&self.solution);
+        }
+        loop {
+            let frame = self.stack.last_mut()?;
+            if let Some(index) = frame.placed.take() {
+                self.used.difference_with(&search.masks[index]);
+                if let Some(blocks) = search.crossings.get(index) {
+                    self.crossed.difference_with(blocks);
+                }
+                self.solution.pop();
+            }
+            if frame.next == frame.end {
+                self.stack.pop();
+                hook.exhausted();
+                continue;
+            }
+            let index = search.by_cell[frame.cell][frame.next];
+            frame.next += 1;
+            let mask = &search.masks[index];
+            let blocks = search.crossings.get(index);
+            if mask.overlaps(&self.used) || blocks.is_some_and(|blocks| blocks.overlaps(&self.crossed)) {
+                hook.refused(index, Refusal::Overlap, &self.solution);
+                continue;
+            }
+            self.stats.nodes += 1;
+            self.used.union_with(mask);
+            if let Some(refusal) = Strands::dead_region(search, &self.used, mask, &mut self.stats) {
+                self.used.difference_with(mask);
+                hook.refused(index, refusal, &self.solution);
+                continue;
+            }
+            frame.placed = Some(index);
+            if let Some(blocks) = blocks {
+                self.crossed.union_with(blocks);
+            }
+            self.solution.push(index);
+            search.budget.offer_partial(&self.solution, self.used.count());
+            match self.enter(search, hook) {
+                Ok(None) => {
+                    hook.placed(index);
+                    hook.solved();
+                    return Some(&self.solution);
+                }
+                Ok(Some(cell)) => {
+                    hook.placed(index);
+                    hook.branch(cell);
+                }
+                Err(refusal) => hook.refused(index, refusal, &self.solution),
+            }
+        }
+    }
+
+    fn enter(&mut self, search: &SolutionSearch, hook: &impl SearchHook) -> Result<Option<usize>, Refusal> {
+        // Reached a new node with `solution` placed. Ok(None) if it's a tiling,
+        // otherwise pushes a frame for its first open cell and returns the cell.
+        if hook.cancelled() || !search.budget.expand() {
+            self.stack.clear();
+            return Err(Refusal::Stopped);
+        }
+        let Some(cell) = self.used.first_missing(&search.full) else {
+            // full coverage of the puzzle, only valid if enough words were used
+            return if self.solution.len() >= search.bounds.0 { Ok(None) } else { Err(Refusal::TooFewAnswers) };
+        };
+        if self.solution.len() == search.bounds.1 {
+            return Err(Refusal::TooManyAnswers);
+        }
+        // The first uncovered cell has to be covered by some word, so only branch on words covering it.
+        // Every tiling is then built in exactly one order, so no permutations of the same tiling are visited.
+        self.stack.push(Frame { cell, next: 0, end: search.by_cell[cell].len(), placed: None });
+        Ok(Some(cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cursor, Refusal, SearchHook};
+    use crate::bitboard::CellSet;
+    use crate::strands::options::Budget;
+    use crate::strands::Strands;
+
+    // Writes the steps down as text
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl SearchHook for Log {
+        fn refused(&mut self, index: usize, refusal: Refusal, _solution: &[usize]) {
+            self.0.push(format!("refused {} {:?}", index, refusal));
+        }
+
+        fn placed(&mut self, index: usize) {
+            self.0.push(format!("placed {}", index));
+        }
+
+        fn branch(&mut self, cell: usize) {
+            self.0.push(format!("branch {}", cell));
+        }
+
+        fn solved(&mut self) {
+            self.0.push("solved".to_string());
+        }
+
+        fn exhausted(&mut self) {
+            self.0.push("exhausted".to_string());
+        }
+    }
+
+    #[test]
+    fn test_cursor_steps() {
+        //AB
+        //CD
+        // "ABC" leaves "D" on its own, "AB" then "CD" tiles the board
+        let strands = Strands::new("AB\nCD".to_string(), None);
+        let masks = vec![
+            strands.coords_to_mask(&[(0, 0), (0, 1), (1, 0)]),
+            strands.coords_to_mask(&[(0, 0), (0, 1)]),
+            strands.coords_to_mask(&[(1, 0), (1, 1)]),
+        ];
+        let search = strands.solution_search(masks, usize::MAX, Budget::unlimited());
+        let mut log = Log::default();
+        let mut cursor = Cursor::new(&search, vec![], CellSet::empty(search.num_cells), &mut log);
+        assert_eq!(cursor.next(&search, &mut log), Some(&[1, 2][..]));
+        assert_eq!(cursor.next(&search, &mut log), None);
+        assert_eq!(log.0, vec![
+            "branch 0",
+            "refused 0 SmallRegion",
+            "placed 1",
+            "branch 2",
+            "refused 0 Overlap",
+            "placed 2",
+            "solved",
+            "exhausted",
+            "exhausted",
+        ]);
+        assert_eq!(cursor.stats.nodes, 3);
+    }
+}
@@ -0,0 +1,129 @@
+// Words the game has rejected, so the solver never uses them again.
+
+use std::collections::HashSet;
+
+use super::{Candidate, Strands};
+use crate::model::Path;
+use crate::trie::Trie;
+
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct Exclusions {
+    words: HashSet<String>, // excluded along any path
+    // excluded on these cells only, kept as the sorted cells so the same word read the other way round
+    // ("EYE", or any path a palindrome retraces) is excluded too
+    paths: HashSet<Candidate>,
+}
+
+impl Exclusions {
+    pub fn exclude_word(&mut self, word: &str) {
+        // case agnostic, like the trie
+        self.words.insert(word.to_ascii_lowercase());
+    }
+
+    pub fn exclude_path(&mut self, word: &str, path: &Path) {
+        self.paths.insert((word.to_ascii_lowercase(), sorted(path.cells())));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.paths.is_empty()
+    }
+
+    pub(crate) fn is_excluded(&self, candidate: &Candidate) -> bool {
+        let word = candidate.0.to_ascii_lowercase();
+        if self.words.contains(&word) {
+            return true;
+        }
+        self.paths.contains(&(word, sorted(candidate.1.clone())))
+    }
+}
+
+fn sorted(mut cells: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+    cells.sort_unstable();
+    cells
+}
+
+impl Strands {
+    pub fn with_excluded_word(mut self, word: &str) -> Self {
+        self.excluded.exclude_word(word);
+        self
+    }
+
+    pub fn with_excluded_path(mut self, word: &str, path: &Path) -> Self {
+        self.excluded.exclude_path(word, path);
+        self
+    }
+
+    pub fn unsolvable_due_to_exclusions(&self, trie: &Trie) -> bool {
+        // True when the board has no solution with the current exclusions, but would without them.
+        // Meant for after `solve` comes back empty, to tell a bad exclusion apart from a board the dictionary can't solve.
+        if self.excluded.is_empty() || self.count_solutions(trie, 1) > 0 {
+            return false;
+        }
+        let unrestricted = Strands {
+            excluded: Exclusions::default(),
+            ..self.clone()
+        };
+        unrestricted.count_solutions(trie, 1) > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Path;
+    use crate::strands::{Rules, Strands};
+    use crate::strands::tests::cat_dog_bee;
+    use crate::trie::Trie;
+
+    #[test]
+    fn test_exclude_word() {
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        assert_eq!(strands.count_solutions(&trie, 10), 2);
+        let strands = strands.with_excluded_word("catgodbee");
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+        let result = strands.solve(&trie).unwrap();
+        assert!(!result.contains_word("CATGODBEE"));
+        assert!(!strands.unsolvable_due_to_exclusions(&trie));
+    }
+
+    #[test]
+    fn test_exclude_path() {
+        //SEES
+        // "SEE" fits twice, excluding one path leaves the other
+        let strands = Strands::new("SEES".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("SEE".to_string());
+        trie.insert("S".to_string());
+        assert_eq!(strands.count_solutions(&trie, 10), 2);
+        let strands = strands.with_excluded_path("SEE", &Path::from(vec![(0, 0), (0, 1), (0, 2)]));
+        let result = strands.solve(&trie).unwrap();
+        assert_eq!(result["SEE"], vec![(0, 3), (0, 2), (0, 1)]);
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+    }
+
+    #[test]
+    fn test_exclude_palindrome() {
+        //ABA
+        // the path read backwards spells the same word on the same cells, so it's excluded as well
+        let strands = Strands::new("ABA".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("ABA".to_string());
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+        let strands = strands.with_excluded_path("ABA", &Path::from(vec![(0, 0), (0, 1), (0, 2)]));
+        assert_eq!(strands.solve(&trie), None);
+        assert!(strands.find_candidates(&trie).is_empty());
+    }
+
+    #[test]
+    fn test_exclusions_make_unsolvable() {
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let strands = strands.with_excluded_word("CATGODBEE").with_excluded_word("DOG");
+        assert_eq!(strands.solve(&trie), None);
+        assert!(strands.unsolvable_due_to_exclusions(&trie));
+
+        // a board that can't be solved anyway isn't blamed on the exclusions
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        let strands = Strands::new("CAT\nDOG".to_string(), None).with_excluded_word("CAT");
+        assert!(!strands.unsolvable_due_to_exclusions(&trie));
+    }
+}
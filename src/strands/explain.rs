@@ -0,0 +1,416 @@
+// A traced run of the cover search, for finding out why the solver picked (or couldn't find) a tiling.
+// It's the same search as `solve`, with a hook that remembers what was tried along the way.
+
+use std::fmt;
+
+use super::cover::{Cursor, Refusal, SearchHook};
+use super::options::Budget;
+use super::{SearchStats, SolutionSearch, SolveOptions, StopReason, Strands};
+use crate::bitboard::CellSet;
+use crate::model::{Coord, Placement, Solution};
+use crate::trie::Trie;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Rejection {
+    Overlap(String), // shares a cell with this earlier answer
+    Crossing(String), // crosses this earlier answer diagonally, with `Rules::no_crossing`
+    SmallRegion, // leaves a region smaller than the shortest word
+    UnfillableRegion, // leaves a region no word fits inside
+    TooFewAnswers, // finishes the board with fewer answers than allowed
+    TooManyAnswers, // uses the last allowed answer with cells still open
+    DeadEnd { nodes: usize }, // nothing below it leads to a tiling, after trying this many placements
+    Budget(StopReason), // the solve ran out of budget here
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::Overlap(word) => write!(f, "overlaps {}", word),
+            Rejection::Crossing(word) => write!(f, "crosses {}", word),
+            Rejection::SmallRegion => write!(f, "leaves a region smaller than any word"),
+            Rejection::UnfillableRegion => write!(f, "leaves a region no word fits"),
+            Rejection::TooFewAnswers => write!(f, "covers the board with too few answers"),
+            Rejection::TooManyAnswers => write!(f, "needs more answers than allowed"),
+            Rejection::DeadEnd { nodes } => write!(f, "dead end after {} placements", nodes),
+            Rejection::Budget(reason) => write!(f, "stopped: {:?}", reason),
+        }
+    }
+}
+
+// One placement the search tried, and what came of it
+#[derive(PartialEq, Debug, Clone)]
+pub struct TraceNode {
+    pub placement: Placement,
+    pub rejected: Option<Rejection>, // None for the placements of the chosen tiling
+    pub cell: Option<Coord>, // the open cell `children` were tried for, None when nothing was tried below
+    // Below a dead end only the next level is kept, so the tree stays small but still shows what blocked each try
+    pub children: Vec<TraceNode>,
+    pub omitted: usize, // rejected children left out of `children`
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Trace {
+    pub solution: Option<Solution>,
+    pub stopped: Option<StopReason>,
+    pub stats: SearchStats,
+    pub cell: Option<Coord>, // the first open cell, which `branches` were tried for
+    pub branches: Vec<TraceNode>,
+    pub omitted: usize,
+}
+
+// The placements tried below one placement of the search, while it's being searched
+struct Level {
+    node: Option<TraceNode>, // the placement, None for the top of the search
+    nodes_before: usize, // `Tracer::nodes` right after it was placed
+    cell: Option<Coord>,
+    nodes: Vec<TraceNode>,
+    omitted: usize,
+}
+
+// Builds the trace from the steps of the cover search
+struct Tracer<'a> {
+    strands: &'a Strands,
+    search: &'a SolutionSearch,
+    placements: &'a [Placement],
+    max_branches: usize,
+    nodes: usize, // placements tried, counted the same way as `SearchStats::nodes`
+    levels: Vec<Level>,
+}
+
+impl Strands {
+    pub fn explain(&self, trie: &Trie, options: &SolveOptions, max_branches: usize) -> Trace {
+        // Solves like `solve_with_options`, recording every placement tried on the way to the first tiling.
+        // At each depth up to `max_branches` rejected placements are kept, the rest are only counted.
+        // Branches that didn't work out are only expanded one level, so the trace stays readable.
+        let budget = Budget::new(options);
+        let candidates = self.find_candidates_within(trie, &budget);
+        let mut trace = Trace {
+            solution: None,
+            stopped: budget.stop_reason(),
+            stats: SearchStats::default(),
+            cell: None,
+            branches: vec![],
+            omitted: 0,
+        };
+        if trace.stopped.is_some() {
+            return trace;
+        }
+
+        let placements = self.placements(&candidates);
+        let search = self.candidate_search(&candidates, 1, budget.clone());
+        let top = Level { node: None, nodes_before: 0, cell: None, nodes: vec![], omitted: 0 };
+        let mut tracer = Tracer { strands: self, search: &search, placements: &placements, max_branches, nodes: 0, levels: vec![top] };
+        let mut cursor = Cursor::new(&search, vec![], CellSet::empty(search.num_cells), &mut tracer);
+        let solved = cursor.next(&search, &mut tracer).is_some();
+        trace.stopped = budget.stop_reason();
+        // whatever is still open is either the chosen tiling or where the budget ran out
+        while tracer.levels.len() > 1 {
+            let rejected = match trace.stopped {
+                _ if solved => None,
+                Some(reason) => Some(Rejection::Budget(reason)),
+                None => unreachable!("the search only stops early when it runs out of budget"),
+            };
+            tracer.close(rejected);
+        }
+        let top = tracer.levels.pop().expect("the top level is never closed");
+        if solved {
+            trace.solution = Some(Solution::new(chosen_path(&top.nodes)));
+        }
+        trace.stats = cursor.stats;
+        trace.cell = top.cell;
+        trace.branches = top.nodes;
+        trace.omitted = top.omitted;
+        trace
+    }
+}
+
+fn chosen_path(nodes: &[TraceNode]) -> Vec<Placement> {
+    // the placements of the tiling, following the chosen node down each level
+    let mut path = vec![];
+    let mut nodes = nodes;
+    while let Some(node) = nodes.iter().find(|node| node.rejected.is_none()) {
+        path.push(node.placement.clone());
+        nodes = &node.children;
+    }
+    path
+}
+
+impl Tracer<'_> {
+    fn coord(&self, cell: usize) -> Coord {
+        Coord::new(cell / self.strands.grid.cols(), cell % self.strands.grid.cols())
+    }
+
+    fn add(&mut self, node: TraceNode) {
+        // keeps the first `max_branches` rejected placements at the current level, and always the chosen one
+        let level = self.levels.last_mut().expect("the top level is never closed");
+        if node.rejected.is_none() || level.nodes.len() < self.max_branches {
+            level.nodes.push(node);
+        } else {
+            level.omitted += 1;
+        }
+    }
+
+    fn close(&mut self, rejected: Option<Rejection>) {
+        // Done with the latest placement: it's added to the level above with everything tried below it
+        let level = self.levels.pop().expect("only placements are closed");
+        let mut node = level.node.expect("only placements are closed");
+        node.cell = level.cell;
+        node.children = level.nodes;
+        node.omitted = level.omitted;
+        if rejected.is_some() {
+            for child in &mut node.children {
+                child.cell = None;
+                child.children.clear();
+                child.omitted = 0;
+            }
+        }
+        node.rejected = rejected;
+        self.add(node);
+    }
+
+    fn overlap(&self, index: usize, chosen: &[usize]) -> Rejection {
+        // a shared cell wins over a shared diagonal block, since crossing answers never share a cell
+        let masks = &self.search.masks;
+        match chosen.iter().find(|&&other| masks[other].overlaps(&masks[index])) {
+            Some(&other) => Rejection::Overlap(self.placements[other].word.clone()),
+            None => {
+                let crossings = &self.search.crossings;
+                let other = chosen.iter().find(|&&other| crossings[other].overlaps(&crossings[index])).expect("overlaps some placement");
+                Rejection::Crossing(self.placements[*other].word.clone())
+            }
+        }
+    }
+}
+
+impl SearchHook for Tracer<'_> {
+    fn refused(&mut self, index: usize, refusal: Refusal, solution: &[usize]) {
+        let rejected = match refusal {
+            Refusal::Overlap => self.overlap(index, solution),
+            Refusal::SmallRegion => Rejection::SmallRegion,
+            Refusal::UnfillableRegion => Rejection::UnfillableRegion,
+            Refusal::TooFewAnswers => Rejection::TooFewAnswers,
+            Refusal::TooManyAnswers => Rejection::TooManyAnswers,
+            Refusal::Stopped => Rejection::Budget(self.search.budget.stop_reason().expect("only the budget stops a trace")),
+        };
+        if refusal != Refusal::Overlap {
+            self.nodes += 1;
+        }
+        self.add(TraceNode { placement: self.placements[index].clone(), rejected: Some(rejected), cell: None, children: vec![], omitted: 0 });
+    }
+
+    fn placed(&mut self, index: usize) {
+        self.nodes += 1;
+        let node = TraceNode { placement: self.placements[index].clone(), rejected: None, cell: None, children: vec![], omitted: 0 };
+        self.levels.push(Level { node: Some(node), nodes_before: self.nodes, cell: None, nodes: vec![], omitted: 0 });
+    }
+
+    fn branch(&mut self, cell: usize) {
+        let coord = self.coord(cell);
+        self.levels.last_mut().expect("the top level is never closed").cell = Some(coord);
+    }
+
+    fn exhausted(&mut self) {
+        // nothing below the latest placement leads to a tiling
+        if let Some(level) = self.levels.last().filter(|level| level.node.is_some()) {
+            let nodes = self.nodes - level.nodes_before;
+            self.close(Some(Rejection::DeadEnd { nodes }));
+        }
+    }
+}
+
+impl Trace {
+    pub fn to_json(&self) -> String {
+        // Hand-written to avoid pulling in a serializer for one output format
+        let solution = match &self.solution {
+            Some(solution) => format!("[{}]", solution.words().map(|word| json_string(word)).collect::<Vec<_>>().join(",")),
+            None => "null".to_string(),
+        };
+        let stopped = self.stopped.map_or("null".to_string(), |reason| json_string(&format!("{:?}", reason)));
+        format!(
+            "{{\"solution\":{},\"stopped\":{},\"nodes\":{},\"cell\":{},\"branches\":{},\"omitted\":{}}}",
+            solution,
+            stopped,
+            self.stats.nodes,
+            json_coord(self.cell),
+            json_nodes(&self.branches),
+            self.omitted
+        )
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_coord(coord: Option<Coord>) -> String {
+    coord.map_or("null".to_string(), |coord| format!("[{},{}]", coord.row, coord.col))
+}
+
+fn json_nodes(nodes: &[TraceNode]) -> String {
+    let nodes: Vec<String> = nodes
+        .iter()
+        .map(|node| {
+            let path: Vec<String> = node.placement.path.iter().map(|&coord| json_coord(Some(coord))).collect();
+            let rejected = match &node.rejected {
+                None => "null".to_string(),
+                Some(Rejection::Overlap(word)) => format!("{{\"reason\":\"overlap\",\"with\":{}}}", json_string(word)),
+                Some(Rejection::Crossing(word)) => format!("{{\"reason\":\"crossing\",\"with\":{}}}", json_string(word)),
+                Some(Rejection::SmallRegion) => "{\"reason\":\"small_region\"}".to_string(),
+                Some(Rejection::UnfillableRegion) => "{\"reason\":\"unfillable_region\"}".to_string(),
+                Some(Rejection::TooFewAnswers) => "{\"reason\":\"too_few_answers\"}".to_string(),
+                Some(Rejection::TooManyAnswers) => "{\"reason\":\"too_many_answers\"}".to_string(),
+                Some(Rejection::DeadEnd { nodes }) => format!("{{\"reason\":\"dead_end\",\"nodes\":{}}}", nodes),
+                Some(Rejection::Budget(reason)) => format!("{{\"reason\":\"budget\",\"stop\":{}}}", json_string(&format!("{:?}", reason))),
+            };
+            format!(
+                "{{\"word\":{},\"path\":[{}],\"spangram\":{},\"rejected\":{},\"cell\":{},\"children\":{},\"omitted\":{}}}",
+                json_string(&node.placement.word),
+                path.join(","),
+                node.placement.spangram,
+                rejected,
+                json_coord(node.cell),
+                json_nodes(&node.children),
+                node.omitted
+            )
+        })
+        .collect();
+    format!("[{}]", nodes.join(","))
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // an indented tree, one placement per line, under the cell it was tried for
+        write_branches(f, self.cell, &self.branches, self.omitted, 0)?;
+        match (&self.solution, self.stopped) {
+            (Some(solution), _) => write!(f, "Solved with {} answers after {} placements.", solution.len(), self.stats.nodes),
+            (None, Some(reason)) => write!(f, "Stopped ({:?}) after {} placements.", reason, self.stats.nodes),
+            (None, None) => write!(f, "No tiling found after {} placements.", self.stats.nodes),
+        }
+    }
+}
+
+fn write_branches(f: &mut fmt::Formatter, cell: Option<Coord>, nodes: &[TraceNode], omitted: usize, depth: usize) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    if let Some(cell) = cell {
+        writeln!(f, "{}covering {}:", indent, cell)?;
+    }
+    for node in nodes {
+        let path: Vec<String> = node.placement.path.iter().map(Coord::to_string).collect();
+        let spangram = if node.placement.spangram { " (spangram)" } else { "" };
+        let outcome = node.rejected.as_ref().map_or("chosen".to_string(), Rejection::to_string);
+        writeln!(f, "{}  {}{} {}: {}", indent, node.placement.word, spangram, path.join(" "), outcome)?;
+        write_branches(f, node.cell, &node.children, node.omitted, depth + 1)?;
+    }
+    if omitted > 0 {
+        writeln!(f, "{}  ... {} more rejected", indent, omitted)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rejection;
+    use crate::model::Coord;
+    use crate::strands::{Rules, SolveOptions, StopReason, Strands};
+    use crate::strands::tests::cat_dog_bee;
+    use crate::trie::Trie;
+
+    fn trie(words: &[&str]) -> Trie {
+        let mut trie = Trie::new();
+        for word in words {
+            trie.insert(word.to_string());
+        }
+        trie
+    }
+
+    #[test]
+    fn test_explain_matches_solve() {
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE", "CA"]);
+        let strands = strands.with_answer_range(2, 4);
+        let trace = strands.explain(&trie, &SolveOptions::new(), 10);
+        assert_eq!(trace.solution, strands.solve(&trie));
+        assert_eq!(trace.stats.nodes, 4);
+        assert_eq!(trace.cell, Some(Coord::new(0, 0)));
+        let outcomes: Vec<(&str, Option<Rejection>)> = trace.branches.iter().map(|node| (node.placement.word.as_str(), node.rejected.clone())).collect();
+        assert_eq!(outcomes, vec![
+            ("CA", Some(Rejection::DeadEnd { nodes: 0 })),
+            ("CAT", None),
+        ]);
+        // one level below the dead end shows why nothing fit
+        assert_eq!(trace.branches[0].cell, Some(Coord::new(0, 2)));
+        assert!(trace.branches[0].children.iter().all(|node| node.rejected == Some(Rejection::Overlap("CA".to_string()))));
+        assert_eq!(trace.branches[1].cell, Some(Coord::new(1, 0)));
+        let children: Vec<&str> = trace.branches[1].children.iter().map(|node| node.placement.word.as_str()).collect();
+        assert_eq!(children, vec!["CATGODBEE", "DOG"]);
+        assert_eq!(trace.branches[1].children[0].rejected, Some(Rejection::Overlap("CAT".to_string())));
+    }
+
+    #[test]
+    fn test_explain_rejections() {
+        //AB
+        //BA
+        // "AA" and "BB" cross, so with `no_crossing` nothing tiles the board
+        let trie = trie(&["AA", "BB"]);
+        let strands = Strands::new("AB\nBA".to_string(), None).with_rules(Rules { no_crossing: true, ..Rules::none() });
+        let trace = strands.explain(&trie, &SolveOptions::new(), 10);
+        assert_eq!(trace.solution, None);
+        assert_eq!(trace.branches.len(), 1);
+        assert_eq!(trace.branches[0].rejected, Some(Rejection::DeadEnd { nodes: 0 }));
+        assert_eq!(trace.branches[0].children[0].rejected, Some(Rejection::Crossing("AA".to_string())));
+        assert!(trace.to_string().ends_with("No tiling found after 1 placements."));
+
+        // with one answer allowed, "AB" leaves cells that nothing may cover
+        let trie = super::tests::trie(&["AB", "ABBA", "BA"]);
+        let strands = Strands::new("AB\nBA".to_string(), 1).with_rules(Rules::none());
+        let trace = strands.explain(&trie, &SolveOptions::new(), 10);
+        assert!(trace.solution.as_ref().is_some_and(|solution| solution.contains_word("ABBA")));
+        assert!(trace.branches.iter().any(|node| node.rejected == Some(Rejection::TooManyAnswers)));
+    }
+
+    #[test]
+    fn test_explain_budget() {
+        let (strands, trie) = cat_dog_bee(None, &[]);
+        let trace = strands.explain(&trie, &SolveOptions::new().with_max_nodes(5), 10);
+        assert_eq!(trace.stopped, Some(StopReason::MaxNodes));
+        assert!(trace.solution.is_none() && trace.branches.is_empty());
+    }
+
+    #[test]
+    fn test_explain_output() {
+        //AB
+        //CD
+        let trie = trie(&["AB", "AC", "CD", "BD"]);
+        let strands = Strands::new("AB\nCD".to_string(), 2).with_rules(Rules::none());
+        let trace = strands.explain(&trie, &SolveOptions::new(), 10);
+        assert_eq!(trace.to_string(), "\
+covering (0, 0):
+  AB (spangram) (0, 0) (0, 1): chosen
+  covering (1, 0):
+    AC (spangram) (0, 0) (1, 0): overlaps AB
+    CD (spangram) (1, 0) (1, 1): chosen
+Solved with 2 answers after 2 placements.");
+        assert_eq!(trace.to_json(), concat!(
+            r#"{"solution":["AB","CD"],"stopped":null,"nodes":2,"cell":[0,0],"branches":["#,
+            r#"{"word":"AB","path":[[0,0],[0,1]],"spangram":true,"rejected":null,"cell":[1,0],"children":["#,
+            r#"{"word":"AC","path":[[0,0],[1,0]],"spangram":true,"rejected":{"reason":"overlap","with":"AB"},"cell":null,"children":[],"omitted":0},"#,
+            r#"{"word":"CD","path":[[1,0],[1,1]],"spangram":true,"rejected":null,"cell":null,"children":[],"omitted":0}"#,
+            r#"],"omitted":0}],"omitted":0}"#,
+        ));
+
+        // the chosen placement is always kept
+        let trace = strands.explain(&trie, &SolveOptions::new(), 0);
+        assert_eq!(trace.branches[0].children.len(), 1);
+        assert_eq!(trace.branches[0].omitted, 1);
+        assert!(trace.to_string().contains("... 1 more rejected"));
+    }
+}
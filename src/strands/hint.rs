@@ -0,0 +1,145 @@
+// Hints like the game's: point at one answer the player hasn't found, without giving the rest away.
+
+use super::Strands;
+use crate::frequency::Frequencies;
+use crate::model::{Coord, Placement, Solution};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum HintPick {
+    #[default]
+    First, // the unfound answer that starts first in reading order
+    Easiest, // the most common word, or the shortest without frequencies
+    Hardest, // the rarest word, or the longest without frequencies
+}
+
+// One unfound answer's cells. The word itself isn't part of the hint, only its tiles on request.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Hint {
+    path: Vec<Coord>, // in the order the word is read
+    tiles: Vec<String>,
+}
+
+impl Hint {
+    pub fn cells(&self) -> Vec<Coord> {
+        // in reading order, so the hint doesn't say where the word starts
+        let mut cells = self.path.clone();
+        cells.sort();
+        cells
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.path.len()
+    }
+
+    pub fn reveal(&self, tiles: usize) -> Vec<(Coord, &str)> {
+        // The first `tiles` tiles of the word in order, for hints that give away a little more each time
+        self.path.iter().zip(&self.tiles).take(tiles).map(|(&coord, tile)| (coord, tile.as_str())).collect()
+    }
+}
+
+impl Strands {
+    pub fn hint(&self, solution: &Solution, found: &[String], pick: HintPick, frequencies: Option<&Frequencies>) -> Option<Hint> {
+        // The next hint for a player who has found the `found` answers (in any case), or None once every answer is found.
+        // The spangram is held back until it's the last answer left, since working it out is the point of the puzzle.
+        let unfound: Vec<&Placement> = solution
+            .placements
+            .iter()
+            .filter(|placement| !found.iter().any(|word| word.eq_ignore_ascii_case(&placement.word)))
+            .collect();
+        let themed: Vec<&Placement> = unfound.iter().copied().filter(|placement| !placement.spangram).collect();
+        let remaining = if themed.is_empty() { unfound } else { themed };
+
+        // higher is easier; ties keep reading order
+        let ease = |placement: &Placement| match frequencies {
+            Some(frequencies) => frequencies.word_score(&placement.word),
+            None => -(placement.word.chars().count() as f64),
+        };
+        let placement = match pick {
+            HintPick::First => remaining.first().copied(),
+            HintPick::Easiest => remaining.iter().copied().rev().max_by(|a, b| ease(a).total_cmp(&ease(b))),
+            HintPick::Hardest => remaining.iter().copied().min_by(|a, b| ease(a).total_cmp(&ease(b))),
+        }?;
+        Some(Hint {
+            path: placement.path.coords().to_vec(),
+            tiles: placement.path.iter().map(|&coord| self.grid[coord].clone()).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HintPick;
+    use crate::frequency::Frequencies;
+    use crate::model::{Coord, Solution};
+    use crate::strands::{Rules, Strands};
+    use crate::trie::Trie;
+
+    fn solved() -> (Strands, Solution) {
+        //CATS
+        //DOGY
+        //BEEX
+        // "CDB" runs down the left side and is the only spangram
+        let strands = Strands::new("CATS\nDOGY\nBEEX".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        for word in ["CDB", "ATS", "OGY", "EEX"] {
+            trie.insert(word.to_string());
+        }
+        let solution = strands.solve(&trie).unwrap();
+        (strands, solution)
+    }
+
+    #[test]
+    fn test_hint_first() {
+        let (strands, solution) = solved();
+        let hint = strands.hint(&solution, &[], HintPick::First, None).unwrap();
+        assert_eq!(hint.cells(), vec![Coord::new(0, 1), Coord::new(0, 2), Coord::new(0, 3)]);
+        assert_eq!(hint.num_cells(), 3);
+
+        let found = ["ats".to_string()];
+        let hint = strands.hint(&solution, &found, HintPick::First, None).unwrap();
+        assert_eq!(hint.reveal(2), vec![(Coord::new(1, 1), "O"), (Coord::new(1, 2), "G")]);
+        assert_eq!(hint.reveal(10).len(), 3);
+    }
+
+    #[test]
+    fn test_hint_spangram_last() {
+        let (strands, solution) = solved();
+        let found: Vec<String> = ["ATS", "OGY", "EEX"].iter().map(|word| word.to_string()).collect();
+        let hint = strands.hint(&solution, &found, HintPick::First, None).unwrap();
+        assert_eq!(hint.cells(), vec![Coord::new(0, 0), Coord::new(1, 0), Coord::new(2, 0)]);
+
+        let found: Vec<String> = solution.words().cloned().collect();
+        assert_eq!(strands.hint(&solution, &found, HintPick::First, None), None);
+    }
+
+    #[test]
+    fn test_hint_difficulty() {
+        let (strands, solution) = solved();
+        let frequencies = Frequencies::parse("ats 50\nogy 3000\neex 10");
+        let easiest = strands.hint(&solution, &[], HintPick::Easiest, Some(&frequencies)).unwrap();
+        assert_eq!(easiest.reveal(1), vec![(Coord::new(1, 1), "O")]);
+        let hardest = strands.hint(&solution, &[], HintPick::Hardest, Some(&frequencies)).unwrap();
+        assert_eq!(hardest.reveal(1), vec![(Coord::new(2, 1), "E")]);
+
+        // without frequencies every answer is the same length, so reading order decides
+        let easiest = strands.hint(&solution, &[], HintPick::Easiest, None).unwrap();
+        assert_eq!(easiest.reveal(1), vec![(Coord::new(0, 1), "A")]);
+    }
+
+    #[test]
+    fn test_hint_difficulty_tiles() {
+        //[QU]IT
+        //ABC
+        // both answers cover three cells, but QUIT has more letters to find
+        let strands = Strands::new("[QU]IT\nABC".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        for word in ["QUIT", "ABC"] {
+            trie.insert(word.to_string());
+        }
+        let solution = strands.solve(&trie).unwrap();
+        let easiest = strands.hint(&solution, &[], HintPick::Easiest, None).unwrap();
+        assert_eq!(easiest.reveal(1), vec![(Coord::new(1, 0), "A")]);
+        let hardest = strands.hint(&solution, &[], HintPick::Hardest, None).unwrap();
+        assert_eq!(hardest.reveal(1), vec![(Coord::new(0, 0), "QU")]);
+    }
+}
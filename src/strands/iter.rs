@@ -0,0 +1,137 @@
+// Tilings handed out one at a time, so the caller decides when to stop instead of the solver.
+// Same search as `solve_all`, paused after each tiling.
+
+use super::cover::Cursor;
+use super::options::Budget;
+use super::observer::Phase;
+use super::{SearchStats, SolutionSearch, SolveOptions, StopReason, Strands};
+use crate::bitboard::CellSet;
+use crate::model::{Placement, Solution};
+use crate::trie::Trie;
+
+pub struct Solutions {
+    placements: Vec<Placement>, // one per candidate
+    search: Option<(SolutionSearch, Cursor)>, // None once the search is over, or if it never started
+    stats: SearchStats, // final counts, once the search is over
+    stopped: Option<StopReason>, // why the iteration ended early, if it did
+}
+
+impl Strands {
+    pub fn solutions(&self, trie: &Trie, options: &SolveOptions) -> Solutions {
+        // Every tiling, found lazily as the iterator is advanced. Candidates are found up front.
+        // The options' limits cover the whole iteration; once one is hit the iterator just ends.
+        // Always searches on one thread, even with `parallel`.
+        let budget = Budget::new(options);
+        budget.observe(|observer| observer.phase(Phase::FindingWords));
+        let candidates = self.find_candidates_within(trie, &budget);
+        let mut solutions = Solutions { placements: vec![], search: None, stats: SearchStats::default(), stopped: budget.stop_reason() };
+        if solutions.stopped.is_some() {
+            return solutions;
+        }
+        let placements = self.placements(&candidates);
+        budget.observe(|observer| observer.candidates_found(&placements));
+        budget.observe(|observer| observer.phase(Phase::Covering));
+
+        let search = self.candidate_search(&candidates, usize::MAX, budget);
+        let cursor = Cursor::new(&search, vec![], CellSet::empty(search.num_cells), &mut ());
+        solutions.placements = placements;
+        solutions.search = Some((search, cursor));
+        solutions
+    }
+}
+
+impl Solutions {
+    pub fn stats(&self) -> &SearchStats {
+        self.search.as_ref().map_or(&self.stats, |(_, cursor)| &cursor.stats)
+    }
+
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        // Some once a limit in the options ended the iteration, so it doesn't mean there are no more tilings
+        self.stopped
+    }
+
+    fn finish(&mut self) {
+        if let Some((search, cursor)) = self.search.take() {
+            self.stats = cursor.stats;
+            self.stopped = search.budget.stop_reason();
+            search.budget.observe(|observer| observer.finished(&self.stats));
+        }
+    }
+}
+
+impl Iterator for Solutions {
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Solution> {
+        let (search, cursor) = self.search.as_mut()?;
+        let Some(indices) = cursor.next(search, &mut ()) else {
+            self.finish();
+            return None;
+        };
+        let solution = Solution::new(indices.iter().map(|&index| self.placements[index].clone()).collect());
+        search.budget.observe(|observer| observer.solution_found(&solution));
+        Some(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::strands::{SolveOptions, StopReason, Strands};
+    use crate::strands::tests::cat_dog_bee;
+    use crate::trie::Trie;
+
+    #[test]
+    fn test_solutions_match_solve_all() {
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let lazy: Vec<_> = strands.solutions(&trie, &SolveOptions::new()).collect();
+        assert_eq!(lazy.len(), 2);
+        assert_eq!(lazy, strands.solve_all(&trie));
+    }
+
+    #[test]
+    fn test_solutions_many() {
+        // lots of tilings, found in the same order as `solve_all`
+        let strands = Strands::new("AAAA\nAAAA\nAAAA".to_string(), None);
+        let mut trie = Trie::new();
+        trie.insert("AA".to_string());
+        trie.insert("AAAA".to_string());
+        let all = strands.solve_all(&trie);
+        assert!(all.len() > 100);
+        assert!(strands.solutions(&trie, &SolveOptions::new()).eq(all));
+    }
+
+    #[test]
+    fn test_solutions_take() {
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let mut solutions = strands.solutions(&trie, &SolveOptions::new());
+        let first = solutions.next().unwrap();
+        assert_eq!(Some(first), strands.solve(&trie));
+        let nodes = solutions.stats().nodes;
+        assert!(solutions.next().is_some());
+        assert!(solutions.stats().nodes > nodes);
+        assert_eq!(solutions.next(), None);
+        assert_eq!(solutions.next(), None);
+    }
+
+    #[test]
+    fn test_solutions_bounds() {
+        // only the three word tiling has the right answer count
+        let (strands, trie) = cat_dog_bee(3, &["CATGODBEE"]);
+        let solutions: Vec<_> = strands.solutions(&trie, &SolveOptions::new()).collect();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].len(), 3);
+    }
+
+    #[test]
+    fn test_solutions_none() {
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        let strands = Strands::new("CAT\nDOG".to_string(), None);
+        assert_eq!(strands.solutions(&trie, &SolveOptions::new()).count(), 0);
+        // a budget that runs out just ends the iteration
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let mut solutions = strands.solutions(&trie, &SolveOptions::new().with_max_nodes(3));
+        assert_eq!(solutions.next(), None);
+        assert_eq!(solutions.stop_reason(), Some(StopReason::MaxNodes));
+    }
+}
@@ -0,0 +1,312 @@
+// Solving with answers the player has already found, so only the rest of the board is searched.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{Budget, Candidate, SearchStats, Strands};
+use crate::model::{Coord, Path, Solution};
+use crate::trie::Trie;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum LockedAnswer {
+    Word(String), // the solver picks the path
+    Placed(String, Path),
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum LockError {
+    LengthMismatch(String), // path and word have a different number of letters
+    OutOfBounds(String, Coord),
+    LetterMismatch(String, Coord),
+    NotAdjacent(String, Coord, Coord),
+    ReusedCell(String, Coord),
+    Overlap(String, String),
+    Crossing(String, String), // with `Rules::no_crossing`, the same word twice when a path crosses itself
+    NotOnBoard(String),
+    WordLength(String), // shorter or longer than `Rules` allow
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LockError::LengthMismatch(word) => write!(f, "Path for {} has the wrong number of cells.", word),
+            LockError::OutOfBounds(word, coord) => write!(f, "Path for {} leaves the grid at {}.", word, coord),
+            LockError::LetterMismatch(word, coord) => write!(f, "Path for {} has the wrong letter at {}.", word, coord),
+            LockError::NotAdjacent(word, from, to) => write!(f, "Path for {} jumps from {} to {}.", word, from, to),
+            LockError::ReusedCell(word, coord) => write!(f, "Path for {} uses {} twice.", word, coord),
+            LockError::Overlap(first, second) => write!(f, "{} and {} share cells.", first, second),
+            LockError::Crossing(first, second) => write!(f, "{} and {} cross.", first, second),
+            LockError::NotOnBoard(word) => write!(f, "{} can't be traced on the grid.", word),
+            LockError::WordLength(word) => write!(f, "{} is too short or too long for the rules.", word),
+        }
+    }
+}
+
+impl std::error::Error for LockError {}
+
+impl Strands {
+    pub fn solve_with_locked(&self, trie: &Trie, locked: &[LockedAnswer]) -> Result<Option<Solution>, LockError> {
+        // Solves the board around answers that are already known. Locked answers don't need to be in the dictionary.
+        // Answers given without a path try each way the word can be traced, until one leaves a solvable board.
+        let mut placed = vec![];
+        let mut unplaced = vec![];
+        for answer in locked {
+            let (LockedAnswer::Word(word) | LockedAnswer::Placed(word, _)) = answer;
+            if !self.rules.allows_len(word.chars().count()) {
+                return Err(LockError::WordLength(word.clone()));
+            }
+            match answer {
+                LockedAnswer::Placed(word, path) => {
+                    self.check_locked_path(word, path)?;
+                    placed.push((word.clone(), path.cells()));
+                }
+                LockedAnswer::Word(word) => {
+                    let paths = self.find_word_paths(word);
+                    if paths.is_empty() {
+                        return Err(LockError::NotOnBoard(word.clone()));
+                    }
+                    unplaced.push(paths);
+                }
+            }
+        }
+        for (index, (first, first_path)) in placed.iter().enumerate() {
+            for (second, second_path) in &placed[index + 1..] {
+                if self.coords_to_mask(first_path).overlaps(&self.coords_to_mask(second_path)) {
+                    return Err(LockError::Overlap(first.clone(), second.clone()));
+                }
+                if self.crossing_mask(first_path).overlaps(&self.crossing_mask(second_path)) {
+                    return Err(LockError::Crossing(first.clone(), second.clone()));
+                }
+            }
+        }
+
+        let candidates = self.candidates(trie);
+        Ok(self.recurse_locked_paths(&candidates, &mut placed, &unplaced, &mut SearchStats::default()))
+    }
+
+    fn recurse_locked_paths(&self, candidates: &[Candidate], placed: &mut Vec<Candidate>, unplaced: &[Vec<Candidate>], stats: &mut SearchStats) -> Option<Solution> {
+        // picks a path for each word in `unplaced` that doesn't overlap or cross the answers placed so far, then solves the rest
+        let Some((paths, rest)) = unplaced.split_first() else {
+            return self.search_solutions(candidates, placed, 1, &Budget::unlimited(), stats).pop();
+        };
+        for path in paths {
+            let (mask, crossing) = (self.coords_to_mask(&path.1), self.crossing_mask(&path.1));
+            if placed.iter().any(|(_, coords)| self.coords_to_mask(coords).overlaps(&mask) || self.crossing_mask(coords).overlaps(&crossing)) {
+                continue;
+            }
+            placed.push(path.clone());
+            if let Some(result) = self.recurse_locked_paths(candidates, placed, rest, stats) {
+                return Some(result);
+            }
+            placed.pop();
+        }
+        None
+    }
+
+    fn check_locked_path(&self, word: &str, path: &Path) -> Result<(), LockError> {
+        // each cell's tile has to match the next letters of the word, and together they have to spell all of it
+        let mut rest = word;
+        let mut seen = HashSet::new();
+        for (index, &coord) in path.iter().enumerate() {
+            if self.is_blocked(coord) {
+                return Err(LockError::OutOfBounds(word.to_string(), coord));
+            }
+            if rest.is_empty() {
+                return Err(LockError::LengthMismatch(word.to_string()));
+            }
+            let tile = &self.grid[coord];
+            match rest.get(..tile.len()) {
+                Some(letters) if letters.eq_ignore_ascii_case(tile) => rest = &rest[tile.len()..],
+                _ => return Err(LockError::LetterMismatch(word.to_string(), coord)),
+            }
+            if !seen.insert(coord) {
+                return Err(LockError::ReusedCell(word.to_string(), coord));
+            }
+            if index > 0 {
+                let previous = path.coords()[index - 1];
+                if !self.adjacency.are_adjacent(previous, coord, &self.grid) {
+                    return Err(LockError::NotAdjacent(word.to_string(), previous, coord));
+                }
+            }
+        }
+        if !rest.is_empty() {
+            return Err(LockError::LengthMismatch(word.to_string()));
+        }
+        if self.rules.no_crossing && self.crosses_itself(&path.cells()) {
+            return Err(LockError::Crossing(word.to_string(), word.to_string()));
+        }
+        Ok(())
+    }
+
+    fn find_word_paths(&self, word: &str) -> Vec<Candidate> {
+        // every distinct set of cells `word` can be traced through, leaving out paths that cross themselves when that's ruled out
+        let mut trie = Trie::new();
+        trie.insert(word.to_string());
+        let mut seen = HashSet::new();
+        let mut paths = vec![];
+        for start in self.grid.coords() {
+            for (_, coords) in self.words_from(&trie, (start.row, start.col), &Budget::unlimited()).0 {
+                if self.rules.no_crossing && self.crosses_itself(&coords) {
+                    continue;
+                }
+                if seen.insert(self.coords_to_mask(&coords)) {
+                    paths.push((word.to_string(), coords));
+                }
+            }
+        }
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Coord, Path};
+    use crate::strands::{LockError, LockedAnswer, Rules, Strands};
+    use crate::strands::tests::cat_dog_bee;
+    use crate::trie::Trie;
+
+    #[test]
+    fn test_locked_placed() {
+        let (strands, trie) = cat_dog_bee(3, &["CATGODBEE"]);
+        let locked = vec![LockedAnswer::Placed("DOG".to_string(), Path::from(vec![(1, 0), (1, 1), (1, 2)]))];
+        let result = strands.solve_with_locked(&trie, &locked).unwrap().unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["DOG"], vec![(1, 0), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_locked_word_not_in_dictionary() {
+        // locked answers don't need to be in the dictionary
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let locked = vec![LockedAnswer::Word("GOD".to_string())];
+        let result = strands.solve_with_locked(&trie, &locked).unwrap().unwrap();
+        assert_eq!(result["GOD"], vec![(1, 2), (1, 1), (1, 0)]);
+        assert!(result.contains_word("CAT") && result.contains_word("BEE"));
+    }
+
+    #[test]
+    fn test_locked_word_picks_path() {
+        //EXA
+        //XYX
+        // "EX" can be traced two ways, but one of them runs into the locked "XY"
+        let strands = Strands::new("EXA\nXYX".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("AX".to_string());
+        trie.insert("XY".to_string());
+        let locked = vec![
+            LockedAnswer::Word("EX".to_string()),
+            LockedAnswer::Placed("XY".to_string(), Path::from(vec![(1, 0), (1, 1)])),
+        ];
+        let result = strands.solve_with_locked(&trie, &locked).unwrap().unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["EX"], vec![(0, 0), (0, 1)]);
+        assert_eq!(result["AX"], vec![(0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn test_locked_unsolvable() {
+        // locking the long word leaves nothing for the other two answers
+        let (strands, trie) = cat_dog_bee(3, &["CATGODBEE"]);
+        let locked = vec![LockedAnswer::Word("CATGODBEE".to_string())];
+        assert_eq!(strands.solve_with_locked(&trie, &locked), Ok(None));
+    }
+
+    #[test]
+    fn test_locked_errors() {
+        let (strands, trie) = cat_dog_bee(3, &["CATGODBEE"]);
+        let check = |answer: LockedAnswer| strands.solve_with_locked(&trie, &[answer]).unwrap_err();
+        assert_eq!(
+            check(LockedAnswer::Placed("CAT".to_string(), Path::from(vec![(0, 0), (0, 1)]))),
+            LockError::LengthMismatch("CAT".to_string())
+        );
+        assert_eq!(
+            check(LockedAnswer::Placed("CAT".to_string(), Path::from(vec![(0, 0), (0, 1), (0, 3)]))),
+            LockError::OutOfBounds("CAT".to_string(), Coord::new(0, 3))
+        );
+        assert_eq!(
+            check(LockedAnswer::Placed("COT".to_string(), Path::from(vec![(0, 0), (0, 1), (0, 2)]))),
+            LockError::LetterMismatch("COT".to_string(), Coord::new(0, 1))
+        );
+        assert_eq!(
+            check(LockedAnswer::Placed("CAB".to_string(), Path::from(vec![(0, 0), (0, 1), (2, 0)]))),
+            LockError::NotAdjacent("CAB".to_string(), Coord::new(0, 1), Coord::new(2, 0))
+        );
+        assert_eq!(
+            check(LockedAnswer::Placed("CAC".to_string(), Path::from(vec![(0, 0), (0, 1), (0, 0)]))),
+            LockError::ReusedCell("CAC".to_string(), Coord::new(0, 0))
+        );
+        assert_eq!(
+            check(LockedAnswer::Word("ZEBRA".to_string())),
+            LockError::NotOnBoard("ZEBRA".to_string())
+        );
+        let overlapping = vec![
+            LockedAnswer::Placed("CAT".to_string(), Path::from(vec![(0, 0), (0, 1), (0, 2)])),
+            LockedAnswer::Placed("TOD".to_string(), Path::from(vec![(0, 2), (1, 1), (1, 0)])),
+        ];
+        assert_eq!(
+            strands.solve_with_locked(&trie, &overlapping),
+            Err(LockError::Overlap("CAT".to_string(), "TOD".to_string()))
+        );
+
+        // too short for the game's rules, even though it's on the board
+        let strands = strands.with_rules(Rules::strands());
+        let check = |answer: LockedAnswer| strands.solve_with_locked(&trie, &[answer]).unwrap_err();
+        assert_eq!(check(LockedAnswer::Word("CAT".to_string())), LockError::WordLength("CAT".to_string()));
+        assert_eq!(
+            check(LockedAnswer::Placed("CAT".to_string(), Path::from(vec![(0, 0), (0, 1), (0, 2)]))),
+            LockError::WordLength("CAT".to_string())
+        );
+    }
+
+    #[test]
+    fn test_locked_crossing() {
+        //AB
+        //BA
+        // "AA" and "BB" cross in the middle, which is an error rather than an unsolvable board
+        let strands = Strands::new("AB\nBA".to_string(), None).with_rules(Rules { no_crossing: true, ..Rules::none() });
+        let trie = Trie::new();
+        let locked = vec![
+            LockedAnswer::Placed("AA".to_string(), Path::from(vec![(0, 0), (1, 1)])),
+            LockedAnswer::Placed("BB".to_string(), Path::from(vec![(0, 1), (1, 0)])),
+        ];
+        assert_eq!(strands.solve_with_locked(&trie, &locked), Err(LockError::Crossing("AA".to_string(), "BB".to_string())));
+        // a locked word is only traced along paths that don't cross the placed answers
+        let locked = vec![LockedAnswer::Placed("AA".to_string(), Path::from(vec![(0, 0), (1, 1)])), LockedAnswer::Word("BB".to_string())];
+        assert_eq!(strands.solve_with_locked(&trie, &locked), Ok(None));
+
+        //ABC
+        //DEF
+        // A -> E -> B -> D crosses itself
+        let strands = Strands::new("ABC\nDEF".to_string(), None).with_rules(Rules { no_crossing: true, ..Rules::none() });
+        let locked = vec![LockedAnswer::Placed("AEBD".to_string(), Path::from(vec![(0, 0), (1, 1), (0, 1), (1, 0)]))];
+        assert_eq!(strands.solve_with_locked(&trie, &locked), Err(LockError::Crossing("AEBD".to_string(), "AEBD".to_string())));
+        assert_eq!(
+            strands.solve_with_locked(&trie, &[LockedAnswer::Word("AEBD".to_string())]),
+            Err(LockError::NotOnBoard("AEBD".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_locked_tiles() {
+        //[TH]E
+        //[IN]K
+        let strands = Strands::new("[TH]E\n[IN]K".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("INK".to_string());
+        let the = LockedAnswer::Placed("THE".to_string(), Path::from(vec![(0, 0), (0, 1)]));
+        let result = strands.solve_with_locked(&trie, &[the]).unwrap().unwrap();
+        assert_eq!(result["INK"], vec![(1, 0), (1, 1)]);
+        let result = strands.solve_with_locked(&trie, &[LockedAnswer::Word("the".to_string())]).unwrap().unwrap();
+        assert_eq!(result["the"], vec![(0, 0), (0, 1)]);
+
+        let check = |answer: LockedAnswer| strands.solve_with_locked(&trie, &[answer]).unwrap_err();
+        assert_eq!(
+            check(LockedAnswer::Placed("TE".to_string(), Path::from(vec![(0, 0), (0, 1)]))),
+            LockError::LetterMismatch("TE".to_string(), Coord::new(0, 0))
+        );
+        assert_eq!(
+            check(LockedAnswer::Placed("THEN".to_string(), Path::from(vec![(0, 0), (0, 1)]))),
+            LockError::LengthMismatch("THEN".to_string())
+        );
+    }
+}
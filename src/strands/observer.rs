@@ -0,0 +1,99 @@
+// Hooks for following a solve as it runs, for tools that want to show progress.
+// Every method does nothing by default, so an observer only implements what it needs.
+
+use super::SearchStats;
+use crate::model::{Placement, Solution};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Phase {
+    FindingWords, // walking the dictionary over the board
+    Covering, // searching for a set of candidates that tiles the board
+}
+
+// How many nodes pass between `progress` calls
+pub const PROGRESS_INTERVAL: usize = 10_000;
+
+// Send + Sync since, with `parallel`, it's called from several threads at once
+pub trait SolveObserver: Send + Sync {
+    fn phase(&self, _phase: Phase) {}
+
+    fn candidates_found(&self, _candidates: &[Placement]) {}
+
+    // total nodes expanded so far, over both phases
+    fn progress(&self, _nodes: usize) {}
+
+    fn solution_found(&self, _solution: &Solution) {}
+
+    fn finished(&self, _stats: &SearchStats) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Phase, SolveObserver, PROGRESS_INTERVAL};
+    use crate::model::{Placement, Solution};
+    use crate::strands::{Rules, SearchStats, SolveOptions, SolveOutcome, Strands};
+    use crate::trie::Trie;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl SolveObserver for Recorder {
+        fn phase(&self, phase: Phase) {
+            self.events.lock().unwrap().push(format!("{:?}", phase));
+        }
+
+        fn candidates_found(&self, candidates: &[Placement]) {
+            self.events.lock().unwrap().push(format!("{} candidates", candidates.len()));
+        }
+
+        fn progress(&self, nodes: usize) {
+            self.events.lock().unwrap().push(format!("{} nodes", nodes));
+        }
+
+        fn solution_found(&self, solution: &Solution) {
+            self.events.lock().unwrap().push(format!("{} answers", solution.len()));
+        }
+
+        fn finished(&self, stats: &SearchStats) {
+            self.events.lock().unwrap().push(format!("finished after {} placements", stats.nodes));
+        }
+    }
+
+    #[test]
+    fn test_observer_events() {
+        //CAT
+        //DOG
+        let strands = Strands::new("CAT\nDOG".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        trie.insert("DOG".to_string());
+        let recorder = Arc::new(Recorder::default());
+        let outcome = strands.solve_with_options(&trie, &SolveOptions::new().with_observer(recorder.clone()));
+        assert!(matches!(outcome, SolveOutcome::Solved(_)));
+        assert_eq!(*recorder.events.lock().unwrap(), vec![
+            "FindingWords",
+            "2 candidates",
+            "Covering",
+            "2 answers",
+            "finished after 2 placements",
+        ]);
+    }
+
+    #[test]
+    fn test_observer_progress() {
+        // 15 cells can't be tiled with even length words, but there are lots of ways to try
+        let strands = Strands::new("AAAAA\nAAAAA\nAAAAA".to_string(), None).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        trie.insert("AA".to_string());
+        trie.insert("AAAA".to_string());
+        let recorder = Arc::new(Recorder::default());
+        let options = SolveOptions::new().with_observer(recorder.clone()).with_max_nodes(PROGRESS_INTERVAL * 2);
+        strands.solve_with_options(&trie, &options);
+        let events = recorder.events.lock().unwrap();
+        assert!(events.contains(&format!("{} nodes", PROGRESS_INTERVAL)));
+    }
+}
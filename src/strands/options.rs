@@ -0,0 +1,314 @@
+// Limits on how long a solve may run, so a bad board or a huge dictionary can't search forever.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use super::observer::{SolveObserver, PROGRESS_INTERVAL};
+use super::Strands;
+use crate::model::Solution;
+use crate::trie::Trie;
+
+#[derive(Clone, Default)]
+pub struct SolveOptions {
+    pub timeout: Option<Duration>, // wall clock time for the whole solve
+    pub max_nodes: Option<usize>, // steps of the word search plus placements tried in the solution search
+    pub cancel: Option<CancelToken>,
+    pub observer: Option<Arc<dyn SolveObserver>>,
+    // Shuffles the order words are tried in, to see a different tiling first on ambiguous boards.
+    // The same seed always gives the same order; without one the order is the sorted candidate order.
+    pub seed: Option<u64>,
+}
+
+impl fmt::Debug for SolveOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SolveOptions")
+            .field("timeout", &self.timeout)
+            .field("max_nodes", &self.max_nodes)
+            .field("cancel", &self.cancel)
+            .field("observer", &self.observer.is_some())
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+impl SolveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn with_observer(mut self, observer: Arc<dyn SolveObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+// Cheap to clone, every clone cancels the same solve. Safe to cancel from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum StopReason {
+    Timeout,
+    MaxNodes,
+    Cancelled,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum SolveOutcome {
+    Solved(Solution),
+    NoSolution, // the whole search ran and nothing tiles the board
+    // the search was stopped early; `partial` is the placement covering the most cells seen so far,
+    // empty if the solve stopped before any word was placed
+    BudgetExhausted { reason: StopReason, partial: Solution },
+}
+
+// The running state of a solve's limits, shared by every branch of the search (and every thread, with `parallel`)
+pub(super) struct Budget {
+    deadline: Option<Instant>,
+    max_nodes: Option<usize>,
+    cancel: Option<CancelToken>,
+    observer: Option<Arc<dyn SolveObserver>>,
+    pub(super) seed: Option<u64>,
+    nodes: AtomicUsize, // only counted if something needs the count, see `counting`
+    counting: bool,
+    stopped: OnceLock<StopReason>,
+    best_covered: AtomicUsize, // mirrors `best.0`, so most offers don't need the lock
+    best: Mutex<(usize, Vec<usize>)>, // cells covered and placement indices of the best partial solution
+    partial: Mutex<Solution>, // `best` as words, filled in once the search stops early
+}
+
+impl Budget {
+    pub(super) fn new(options: &SolveOptions) -> Arc<Self> {
+        Arc::new(Budget {
+            deadline: options.timeout.map(|timeout| Instant::now() + timeout),
+            max_nodes: options.max_nodes,
+            cancel: options.cancel.clone(),
+            observer: options.observer.clone(),
+            seed: options.seed,
+            nodes: AtomicUsize::new(0),
+            // the shared counter is contended with `parallel`, so an unlimited solve with no one watching skips it
+            counting: options.max_nodes.is_some() || options.timeout.is_some() || options.observer.is_some(),
+            stopped: OnceLock::new(),
+            best_covered: AtomicUsize::new(0),
+            best: Mutex::new((0, vec![])),
+            partial: Mutex::new(Solution::default()),
+        })
+    }
+
+    pub(super) fn unlimited() -> Arc<Self> {
+        Self::new(&SolveOptions::default())
+    }
+
+    pub(super) fn expand(&self) -> bool {
+        // Counts one node of either search. False once the solve has to stop, and from then on.
+        if self.stopped.get().is_some() {
+            return false;
+        }
+        if !self.counting {
+            if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+                let _ = self.stopped.set(StopReason::Cancelled);
+                return false;
+            }
+            return true;
+        }
+        let nodes = self.nodes.fetch_add(1, Ordering::Relaxed) + 1;
+        if nodes.is_multiple_of(PROGRESS_INTERVAL) {
+            self.observe(|observer| observer.progress(nodes));
+        }
+        let reason = if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            Some(StopReason::Cancelled)
+        } else if self.max_nodes.is_some_and(|max_nodes| nodes > max_nodes) {
+            Some(StopReason::MaxNodes)
+        // reading the clock is the slow part, so only every so often
+        } else if nodes % 256 == 1 && self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(StopReason::Timeout)
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => {
+                let _ = self.stopped.set(reason);
+                false
+            }
+            None => true,
+        }
+    }
+
+    pub(super) fn stop_reason(&self) -> Option<StopReason> {
+        self.stopped.get().copied()
+    }
+
+    pub(super) fn offer_partial(&self, solution: &[usize], covered: usize) {
+        if covered <= self.best_covered.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut best = self.best.lock().unwrap();
+        if covered > best.0 {
+            *best = (covered, solution.to_vec());
+            self.best_covered.store(covered, Ordering::Relaxed);
+        }
+    }
+
+    pub(super) fn best_partial(&self) -> Vec<usize> {
+        self.best.lock().unwrap().1.clone()
+    }
+
+    pub(super) fn set_partial(&self, partial: Solution) {
+        *self.partial.lock().unwrap() = partial;
+    }
+
+    fn take_partial(&self) -> Solution {
+        std::mem::take(&mut self.partial.lock().unwrap())
+    }
+
+    pub(super) fn observe(&self, event: impl FnOnce(&dyn SolveObserver)) {
+        if let Some(observer) = &self.observer {
+            event(observer.as_ref());
+        }
+    }
+}
+
+impl Strands {
+    pub fn solve_with_options(&self, trie: &Trie, options: &SolveOptions) -> SolveOutcome {
+        // Like `solve`, but gives up once a limit in `options` is hit, with the best partial tiling found by then
+        // Progress is reported to the options' observer, if there is one.
+        let budget = Budget::new(options);
+        match (self.solve_within(trie, &budget).0, budget.stop_reason()) {
+            (Some(solution), _) => SolveOutcome::Solved(solution),
+            (None, None) => SolveOutcome::NoSolution,
+            (None, Some(reason)) => SolveOutcome::BudgetExhausted { reason, partial: budget.take_partial() },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use super::{Budget, CancelToken, SolveOptions, SolveOutcome, StopReason};
+    use crate::strands::{Rules, Strands};
+    use crate::strands::tests::cat_dog_bee;
+    use crate::trie::Trie;
+
+    #[test]
+    fn test_unlimited() {
+        let (strands, trie) = cat_dog_bee(3, &[]);
+        let SolveOutcome::Solved(result) = strands.solve_with_options(&trie, &SolveOptions::new()) else {
+            panic!("expected a solution");
+        };
+        assert_eq!(Some(result), strands.solve(&trie));
+
+        let mut trie = Trie::new();
+        trie.insert("CAT".to_string());
+        assert_eq!(strands.solve_with_options(&trie, &SolveOptions::new()), SolveOutcome::NoSolution);
+    }
+
+    #[test]
+    fn test_max_nodes() {
+        let (strands, trie) = cat_dog_bee(3, &[]);
+        let outcome = strands.solve_with_options(&trie, &SolveOptions::new().with_max_nodes(5));
+        assert_eq!(outcome, SolveOutcome::BudgetExhausted { reason: StopReason::MaxNodes, partial: Default::default() });
+    }
+
+    #[test]
+    fn test_max_nodes_partial() {
+        // enough budget to find the candidates and place a word, but not to finish
+        let (strands, trie) = cat_dog_bee(3, &[]);
+        let mut max_nodes = 1;
+        let partial = loop {
+            match strands.solve_with_options(&trie, &SolveOptions::new().with_max_nodes(max_nodes)) {
+                SolveOutcome::BudgetExhausted { partial, .. } if !partial.is_empty() => break partial,
+                SolveOutcome::BudgetExhausted { .. } => max_nodes += 1,
+                outcome => panic!("finished before placing a word: {:?}", outcome),
+            }
+        };
+        assert!(partial.len() < 3);
+        assert!(partial.words().all(|word| ["CAT", "DOG", "BEE"].contains(&word.as_str())));
+    }
+
+    #[test]
+    fn test_seed() {
+        //AB
+        //CD
+        // two tilings, the seed decides which comes first
+        let strands = Strands::new("AB\nCD".to_string(), 2).with_rules(Rules::none());
+        let mut trie = Trie::new();
+        for word in ["AB", "CD", "AC", "BD"] {
+            trie.insert(word.to_string());
+        }
+        let unseeded: Vec<_> = strands.solutions(&trie, &SolveOptions::new()).collect();
+        let seeded = |seed| strands.solutions(&trie, &SolveOptions::new().with_seed(seed)).collect::<Vec<_>>();
+        assert_eq!(seeded(7), seeded(7));
+        assert!((0..20).all(|seed| seeded(seed).len() == 2 && seeded(seed).iter().all(|solution| unseeded.contains(solution))));
+        assert!((0..20).any(|seed| seeded(seed)[0] != unseeded[0]));
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let (strands, trie) = cat_dog_bee(3, &[]);
+        let cancel = CancelToken::new();
+        cancel.clone().cancel();
+        let outcome = strands.solve_with_options(&trie, &SolveOptions::new().with_cancel(cancel));
+        assert_eq!(outcome, SolveOutcome::BudgetExhausted { reason: StopReason::Cancelled, partial: Default::default() });
+    }
+
+    #[test]
+    fn test_unlimited_skips_counting() {
+        let budget = Budget::unlimited();
+        assert!((0..1000).all(|_| budget.expand()));
+        assert_eq!(budget.nodes.load(Ordering::Relaxed), 0);
+        // cancelling still stops it
+        let cancel = CancelToken::new();
+        let budget = Budget::new(&SolveOptions::new().with_cancel(cancel.clone()));
+        assert!(budget.expand());
+        cancel.cancel();
+        assert!(!budget.expand());
+        assert_eq!(budget.stop_reason(), Some(StopReason::Cancelled));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let (strands, trie) = cat_dog_bee(3, &[]);
+        let outcome = strands.solve_with_options(&trie, &SolveOptions::new().with_timeout(Duration::ZERO));
+        assert_eq!(outcome, SolveOutcome::BudgetExhausted { reason: StopReason::Timeout, partial: Default::default() });
+        let outcome = strands.solve_with_options(&trie, &SolveOptions::new().with_timeout(Duration::from_secs(60)));
+        assert!(matches!(outcome, SolveOutcome::Solved(_)));
+    }
+}
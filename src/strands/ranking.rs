@@ -0,0 +1,144 @@
+// Ordering alternative tilings so the most plausible one comes first.
+
+use super::{SolveOptions, Strands};
+use crate::frequency::Frequencies;
+use crate::model::Solution;
+use crate::theme::Associations;
+use crate::trie::Trie;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Ranking {
+    pub solutions: Vec<Solution>, // the best tilings, highest score first
+    pub tilings: usize, // how many tilings were scored
+    pub exhaustive: bool, // false if the options' budget ran out first, so a better tiling may not have been scored
+}
+
+impl Strands {
+    pub fn with_clue(mut self, clue: &str) -> Self {
+        self.clue = Some(clue.to_string());
+        self
+    }
+
+    pub fn theme_score(&self, solution: &Solution, associations: &Associations) -> f64 {
+        // total relatedness of the answers to the clue, 0 when there's no clue
+        match &self.clue {
+            Some(clue) => solution.words().map(|word| associations.relatedness(word, clue)).sum(),
+            None => 0.0,
+        }
+    }
+
+    pub fn solve_themed(&self, trie: &Trie, associations: &Associations, limit: usize) -> Vec<Solution> {
+        // the `limit` tilings that fit the theme best out of every tiling, most thematic first
+        self.solve_ranked(trie, limit, |solution| self.theme_score(solution, associations))
+    }
+
+    pub fn solve_scored(&self, trie: &Trie, frequencies: &Frequencies, limit: usize) -> Vec<Solution> {
+        // the `limit` tilings with the most common words, by word frequency
+        self.solve_ranked(trie, limit, |solution| frequencies.score(solution.words()))
+    }
+
+    pub fn solve_ranked(&self, trie: &Trie, limit: usize, score: impl Fn(&Solution) -> f64) -> Vec<Solution> {
+        // The `limit` highest scoring tilings out of all of them, with their `score` filled in, highest first.
+        // Tilings with equal scores stay in the order the search found them.
+        self.solve_ranked_with_options(trie, &SolveOptions::new(), limit, score).solutions
+    }
+
+    pub fn solve_ranked_with_options(&self, trie: &Trie, options: &SolveOptions, limit: usize, score: impl Fn(&Solution) -> f64) -> Ranking {
+        // Like `solve_ranked`, but only ranks the tilings found before a limit in `options` is hit.
+        // Every tiling has to be scored to know which is best, so on boards with many of them a budget is worth setting.
+        let mut solutions = self.solutions(trie, options);
+        let mut ranking = Ranking { solutions: vec![], tilings: 0, exhaustive: true };
+        for mut solution in solutions.by_ref() {
+            ranking.tilings += 1;
+            let value = score(&solution);
+            solution.score = Some(value);
+            // after every tiling scoring at least as high, so ties keep the search order
+            let position = ranking.solutions.partition_point(|kept| kept.score.unwrap_or(0.0).total_cmp(&value).is_ge());
+            if position < limit {
+                ranking.solutions.insert(position, solution);
+                ranking.solutions.truncate(limit);
+            }
+        }
+        ranking.exhaustive = solutions.stop_reason().is_none();
+        ranking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frequency::Frequencies;
+    use crate::strands::SolveOptions;
+    use crate::strands::tests::cat_dog_bee;
+    use crate::theme::Associations;
+
+    #[test]
+    fn test_solve_themed() {
+        // both tilings are valid, the clue picks the animals
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let associations = Associations::parse("animals: cat, dog, bee, ant");
+        let strands = strands.with_clue("Animals");
+        let ranked = strands.solve_themed(&trie, &associations, 10);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].len(), 3);
+        assert_eq!(ranked[0].score, Some(3.0));
+        assert!(ranked[1].contains_word("CATGODBEE"));
+        assert_eq!(ranked[1].score, Some(0.0));
+    }
+
+    #[test]
+    fn test_solve_themed_whole_search() {
+        // the thematic tiling is found second, but with room for one it still wins
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let associations = Associations::parse("nonsense: catgodbee");
+        let strands = strands.with_clue("Nonsense");
+        assert!(!strands.solve(&trie).unwrap().contains_word("CATGODBEE"));
+        let ranked = strands.solve_themed(&trie, &associations, 1);
+        assert_eq!(ranked.len(), 1);
+        assert!(ranked[0].contains_word("CATGODBEE"));
+        assert_eq!(ranked[0].score, Some(1.0));
+    }
+
+    #[test]
+    fn test_solve_themed_no_clue() {
+        // without a clue every tiling scores 0 and the search order is kept
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let associations = Associations::parse("animals: cat, dog, bee");
+        let ranked = strands.solve_themed(&trie, &associations, 10);
+        let unranked = strands.solve_all(&trie);
+        let ranked: Vec<_> = ranked.into_iter().map(|solution| solution.placements).collect();
+        assert_eq!(ranked, unranked.into_iter().map(|solution| solution.placements).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_solve_scored() {
+        // "catgodbee" isn't in the frequency list, so the tiling of common words wins
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        let frequencies = Frequencies::parse("cat 50000
+dog 40000
+bee 8000");
+        let ranked = strands.solve_scored(&trie, &frequencies, 10);
+        assert_eq!(ranked.len(), 2);
+        assert!(ranked[0].contains_word("CAT"));
+        assert_eq!(ranked[0].score, Some(50000f64.ln() + 40000f64.ln() + 8000f64.ln()));
+        assert!(ranked[1].contains_word("CATGODBEE"));
+        assert_eq!(ranked[1].score, Some(-10.0));
+    }
+
+    #[test]
+    fn test_solve_ranked_whole_search() {
+        // the best tiling is the second one found, so ranking only the first would miss it
+        let (strands, trie) = cat_dog_bee(None, &["CATGODBEE"]);
+        assert!(strands.solve(&trie).unwrap().len() == 3);
+        let ranked = strands.solve_ranked(&trie, 1, |solution| if solution.contains_word("CATGODBEE") { 1.0 } else { 0.0 });
+        assert_eq!(ranked.len(), 1);
+        assert!(ranked[0].contains_word("CATGODBEE"));
+
+        let ranking = strands.solve_ranked_with_options(&trie, &SolveOptions::new(), 1, |_| 0.0);
+        assert_eq!(ranking.tilings, 2);
+        assert!(ranking.exhaustive);
+        // a budget that runs out ranks what was found by then, and says so
+        let ranking = strands.solve_ranked_with_options(&trie, &SolveOptions::new().with_max_nodes(3), 1, |_| 0.0);
+        assert_eq!(ranking.tilings, 0);
+        assert!(!ranking.exhaustive);
+    }
+}
@@ -0,0 +1,250 @@
+// Optional game rules on top of "cover every cell exactly once".
+
+use super::Strands;
+use crate::bitboard::CellSet;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Rules {
+    // Answer paths may not cross diagonally, making an X through the middle of four cells, as in Strands.
+    // Only diagonal steps between touching cells count, so wrapped steps on a torus never cross.
+    pub no_crossing: bool,
+    // Word lengths in letters, so a `[QU]` tile counts as two. The word search stops walking once a word would be too long.
+    // The default is the game's four letters or more, `Rules::none()` allows any length.
+    pub min_word_len: usize,
+    pub max_word_len: Option<usize>,
+}
+
+impl Rules {
+    pub fn strands() -> Self {
+        // the rules of the real game, the default plus `no_crossing`
+        Rules { no_crossing: true, ..Rules::default() }
+    }
+
+    pub fn none() -> Self {
+        // nothing beyond covering every cell once
+        Rules { no_crossing: false, min_word_len: 0, max_word_len: None }
+    }
+
+    pub(super) fn allows_len(&self, len: usize) -> bool {
+        len >= self.min_word_len && self.max_word_len.is_none_or(|max| len <= max)
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        // What `Strands::new` starts with: the game's word lengths, crossing answers allowed
+        Rules { no_crossing: false, min_word_len: 4, max_word_len: None }
+    }
+}
+
+impl Strands {
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub(super) fn diagonal_steps(&self, coords: &[(isize, isize)]) -> Vec<(usize, bool)> {
+        // The 2x2 blocks a path steps diagonally through, numbered in reading order like cells,
+        // and whether the step goes down-right (`\`) rather than down-left (`/`).
+        // A board has (rows - 1) x (cols - 1) blocks, one per cell but the last row and column.
+        let cols = self.grid.cols() as isize - 1;
+        coords
+            .windows(2)
+            .filter_map(|step| {
+                let ((row, col), (next_row, next_col)) = (step[0], step[1]);
+                if (next_row - row).abs() != 1 || (next_col - col).abs() != 1 {
+                    return None;
+                }
+                let block = row.min(next_row) * cols + col.min(next_col);
+                Some((block as usize, (next_row - row) == (next_col - col)))
+            })
+            .collect()
+    }
+
+    pub(super) fn crosses_itself(&self, coords: &[(isize, isize)]) -> bool {
+        let steps = self.diagonal_steps(coords);
+        steps.iter().any(|&(block, down_right)| steps.contains(&(block, !down_right)))
+    }
+
+    pub(super) fn num_blocks(&self) -> usize {
+        // Size of the crossing masks: every 2x2 block with `no_crossing`, none without.
+        // Kept apart from the cell masks, so a 6x8 board's 48 cells and 35 blocks each fit in a single word.
+        if self.rules.no_crossing {
+            self.grid.rows().saturating_sub(1) * self.grid.cols().saturating_sub(1)
+        } else {
+            0
+        }
+    }
+
+    pub(super) fn crossing_mask(&self, coords: &[(isize, isize)]) -> CellSet {
+        // The blocks a placement steps diagonally through, with `no_crossing`.
+        // Two paths stepping diagonally through the same block either share a cell or cross,
+        // so the cover search rules out crossings by checking these for overlaps, the same way it does cells.
+        if !self.rules.no_crossing {
+            return CellSet::empty(0);
+        }
+        CellSet::from_indices(self.num_blocks(), self.diagonal_steps(coords).into_iter().map(|(block, _)| block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rules;
+    use crate::adjacency::Adjacency;
+    use crate::bitboard::CellSet;
+    use crate::model::Solution;
+    use crate::strands::{SolveOptions, SolveOutcome, Strands};
+    use crate::trie::Trie;
+
+    fn no_crossing() -> Rules {
+        Rules { no_crossing: true, ..Rules::none() }
+    }
+
+    #[test]
+    fn test_crossing_pair() {
+        //AB
+        //BA
+        // "AA" and "BB" both go diagonally and cross in the middle, "AB" twice doesn't
+        let mut trie = Trie::new();
+        trie.insert("AA".to_string());
+        trie.insert("BB".to_string());
+        let strands = Strands::new("AB\nBA".to_string(), 2).with_rules(Rules::none());
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+        let strands = strands.with_rules(no_crossing());
+        assert_eq!(strands.count_solutions(&trie, 10), 0);
+
+        trie.insert("AB".to_string());
+        assert_eq!(strands.count_solutions(&trie, 10), 2);
+        let result = strands.solve(&trie).unwrap();
+        assert!(result.contains_word("AB"));
+    }
+
+    #[test]
+    fn test_crossing_picks_tiling() {
+        //ABC
+        //DEF
+        // "AE"+"BD" cross, "AD"+"BE" don't. Both tilings finish with "CF"
+        let mut trie = Trie::new();
+        for word in ["AE", "BD", "AD", "BE", "CF"] {
+            trie.insert(word.to_string());
+        }
+        let strands = Strands::new("ABC\nDEF".to_string(), 3).with_rules(Rules::none());
+        assert_eq!(strands.count_solutions(&trie, 10), 2);
+        let strands = strands.with_rules(no_crossing());
+        let all = strands.solve_all(&trie);
+        assert_eq!(all.len(), 1);
+        assert!(all[0].contains_word("AD") && all[0].contains_word("BE"));
+    }
+
+    #[test]
+    fn test_crossing_torus_wrap() {
+        // a step that wraps around the edge isn't a diagonal through a block
+        let strands = Strands::new("ABC\nDEF\nGHI".to_string(), None).with_adjacency(Adjacency::Torus);
+        assert!(strands.diagonal_steps(&[(0, 0), (2, 2)]).is_empty());
+        assert_eq!(strands.diagonal_steps(&[(0, 1), (1, 0)]), vec![(0, false)]);
+        assert_eq!(strands.diagonal_steps(&[(1, 0), (0, 1)]), vec![(0, false)]);
+        assert_eq!(strands.diagonal_steps(&[(2, 2), (1, 1)]), vec![(3, true)]);
+    }
+
+    #[test]
+    fn test_crossing_masks_small() {
+        // a full size board keeps its cells and its blocks in one word each
+        let strands = Strands::new(["ABCDEFGH"; 6].join("\n"), None).with_rules(no_crossing());
+        assert_eq!(strands.num_blocks(), 35);
+        assert_eq!(strands.crossing_mask(&[(4, 6), (5, 7)]), CellSet::Small(1 << 34));
+        assert!(matches!(strands.coords_to_mask(&[(5, 7)]), CellSet::Small(_)));
+        assert_eq!(Strands::new("AB\nCD".to_string(), None).num_blocks(), 0);
+    }
+
+    #[test]
+    fn test_crossing_small_region() {
+        //XYZ
+        //P..
+        //.Q.
+        // "PQ" steps diagonally, which mustn't count towards its length when pruning small regions
+        let mut trie = Trie::new();
+        trie.insert("XYZ".to_string());
+        trie.insert("PQ".to_string());
+        let strands = Strands::new("XYZ\nP..\n.Q.".to_string(), None).with_rules(no_crossing());
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+    }
+
+    #[test]
+    fn test_crossing_partial() {
+        //ABCD
+        //XEXX
+        //XXIX
+        // the best partial tiling is the one covering the most cells, the diagonal steps of "AEI" don't count
+        let mut trie = Trie::new();
+        for word in ["ABCD", "AEI", "EI", "BC"] {
+            trie.insert(word.to_string());
+        }
+        let strands = Strands::new("ABCD\nXEXX\nXXIX".to_string(), None).with_rules(Rules::none());
+        let partial = |strands: &Strands| match strands.solve_with_options(&trie, &SolveOptions::new().with_max_nodes(59)) {
+            SolveOutcome::BudgetExhausted { partial, .. } => partial,
+            outcome => panic!("expected to run out, got {:?}", outcome),
+        };
+        let words = |solution: Solution| solution.words().cloned().collect::<Vec<_>>();
+        assert_eq!(words(partial(&strands)), vec!["ABCD"]);
+        assert_eq!(words(partial(&strands.with_rules(no_crossing()))), vec!["ABCD"]);
+    }
+
+    #[test]
+    fn test_word_length() {
+        //CATS
+        //HOED
+        let mut trie = Trie::new();
+        for word in ["CAT", "CATS", "HOED", "HOE", "CATSDEOH"] {
+            trie.insert(word.to_string());
+        }
+        let strands = Strands::new("CATS\nHOED".to_string(), None).with_rules(Rules::none());
+        let words = |strands: &Strands| -> Vec<String> {
+            let mut words: Vec<String> = strands.find_candidates(&trie).into_iter().map(|placement| placement.word).collect();
+            words.sort();
+            words
+        };
+        assert_eq!(words(&strands), vec!["CAT", "CATS", "CATSDEOH", "HOE", "HOED"]);
+        let strands = strands.with_rules(Rules { min_word_len: 4, max_word_len: Some(4), ..Rules::none() });
+        assert_eq!(words(&strands), vec!["CATS", "HOED"]);
+        assert!(strands.solve(&trie).is_some_and(|solution| solution.contains_word("CATS")));
+        let strands = strands.with_rules(Rules::strands());
+        assert_eq!(words(&strands), vec!["CATS", "CATSDEOH", "HOED"]);
+    }
+
+    #[test]
+    fn test_word_length_tiles() {
+        // a tile's letters all count, and a long tile can push a word over the limit
+        let mut trie = Trie::new();
+        trie.insert("QUIT".to_string());
+        let strands = Strands::new("[QU]IT".to_string(), None).with_rules(Rules { min_word_len: 4, ..Rules::none() });
+        assert_eq!(strands.find_candidates(&trie).len(), 1);
+        let strands = strands.with_rules(Rules { max_word_len: Some(3), ..Rules::none() });
+        assert!(strands.find_candidates(&trie).is_empty());
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(Strands::new("AB".to_string(), None).rules, Rules::default());
+        assert_eq!(Rules::default(), Rules { no_crossing: false, ..Rules::strands() });
+        assert!(!Rules::default().allows_len(3) && Rules::default().allows_len(4));
+        assert!(Rules::none().allows_len(1));
+    }
+
+    #[test]
+    fn test_crosses_itself() {
+        //ABC
+        //DEF
+        // A -> E -> B -> D steps \ then / through the same block
+        let strands = Strands::new("ABC\nDEF".to_string(), None).with_rules(Rules::none());
+        assert!(strands.crosses_itself(&[(0, 0), (1, 1), (0, 1), (1, 0)]));
+        assert!(!strands.crosses_itself(&[(0, 0), (1, 1), (0, 2)]));
+
+        let mut trie = Trie::new();
+        trie.insert("AEBD".to_string());
+        trie.insert("CF".to_string());
+        assert_eq!(strands.count_solutions(&trie, 10), 1);
+        let strands = strands.with_rules(no_crossing());
+        assert!(strands.find_candidates(&trie).iter().all(|placement| placement.word != "AEBD"));
+        assert_eq!(strands.count_solutions(&trie, 10), 0);
+    }
+}
@@ -0,0 +1,134 @@
+// Solving a board while it's still being typed in or corrected.
+// Each start cell's word walk is kept along with the cells it looked at, so an edit only re-walks the starts whose walk
+// went through the edited cell. Every other start would find exactly the same words on the new board.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::options::Budget;
+use super::{Candidate, SearchStats, Strands};
+use crate::bitboard::CellSet;
+use crate::model::{Coord, GridError, Placement, Solution};
+use crate::trie::Trie;
+
+pub struct Session<'a> {
+    strands: Strands,
+    trie: &'a Trie,
+    walks: Vec<(Vec<Candidate>, CellSet)>, // per start cell in reading order: the words found and the cells looked at
+}
+
+impl<'a> Session<'a> {
+    pub fn new(strands: Strands, trie: &'a Trie) -> Self {
+        let starts: Vec<Coord> = strands.grid.coords().collect();
+        #[cfg(feature = "parallel")]
+        let walks = starts.par_iter().map(|&start| strands.words_from(trie, (start.row, start.col), &Budget::unlimited())).collect();
+        #[cfg(not(feature = "parallel"))]
+        let walks = starts.iter().map(|&start| strands.words_from(trie, (start.row, start.col), &Budget::unlimited())).collect();
+        Session { strands, trie, walks }
+    }
+
+    pub fn strands(&self) -> &Strands {
+        &self.strands
+    }
+
+    pub fn into_strands(self) -> Strands {
+        self.strands
+    }
+
+    pub fn candidates(&self) -> Vec<Placement> {
+        // the same list `Strands::find_candidates` gives for the board as it is now
+        self.strands.placements(&self.current_candidates())
+    }
+
+    pub fn solve(&self) -> Option<Solution> {
+        // Re-runs the cover search on the kept candidates, no words are traced.
+        let candidates = self.current_candidates();
+        self.strands.search_solutions(&candidates, &[], 1, &Budget::unlimited(), &mut SearchStats::default()).pop()
+    }
+
+    pub fn set_tile(&mut self, coord: Coord, tile: &str) -> Result<usize, GridError> {
+        // Changes one tile and traces words again from every start whose walk looked at it, returning how many that was.
+        // Setting a tile to what it already is changes nothing, and one `Grid::set` refuses leaves the board as it was.
+        // Panics if `coord` is off the board.
+        if self.strands.grid[coord].eq_ignore_ascii_case(tile) {
+            return Ok(0);
+        }
+        self.strands.grid.set(coord, tile)?;
+        let cell = self.strands.grid.index(coord);
+        let mut rewalked = 0;
+        for (index, start) in self.strands.grid.coords().enumerate() {
+            if self.walks[index].1.contains(cell) {
+                self.walks[index] = self.strands.words_from(self.trie, (start.row, start.col), &Budget::unlimited());
+                rewalked += 1;
+            }
+        }
+        Ok(rewalked)
+    }
+
+    fn current_candidates(&self) -> Vec<Candidate> {
+        // the kept walks only know the board, exclusions and the crossing rule are applied to them like in `find_candidates`
+        let found = self.walks.iter().flat_map(|(found, _)| found.iter().cloned()).collect();
+        self.strands.filter_candidates(found, &Budget::unlimited())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use crate::model::{Coord, GridError};
+    use crate::strands::{Rules, Strands};
+    use crate::trie::Trie;
+
+    fn trie() -> Trie {
+        let mut trie = Trie::new();
+        for word in ["CDB", "ATS", "OGY", "EEX", "BEE"] {
+            trie.insert(word.to_string());
+        }
+        trie
+    }
+
+    #[test]
+    fn test_session_edit() {
+        //CATS
+        //DOGY
+        //BEEZ  <- typo, should be an X
+        let trie = trie();
+        let mut session = Session::new(Strands::new("CATS\nDOGY\nBEEZ".to_string(), None).with_rules(Rules::none()), &trie);
+        assert_eq!(session.solve(), None);
+
+        let rewalked = session.set_tile(Coord::new(2, 3), "X").unwrap();
+        assert!(rewalked > 0 && rewalked < 12);
+        let fixed = Strands::new("CATS\nDOGY\nBEEX".to_string(), None).with_rules(Rules::none());
+        assert_eq!(session.strands(), &fixed);
+        assert_eq!(session.candidates(), fixed.find_candidates(&trie));
+        assert_eq!(session.solve(), fixed.solve(&trie));
+        assert!(session.solve().unwrap().contains_word("EEX"));
+
+        assert_eq!(session.set_tile(Coord::new(2, 3), "X"), Ok(0));
+    }
+
+    #[test]
+    fn test_session_block() {
+        // blocking a cell drops the words through it, unblocking brings them back
+        let trie = trie();
+        let mut session = Session::new(Strands::new("CATS\nDOGY\nBEEX".to_string(), None), &trie);
+        let before = session.candidates();
+        session.set_tile(Coord::new(2, 1), ".").unwrap();
+        assert!(session.candidates().iter().all(|placement| placement.word != "EEX" && placement.word != "BEE"));
+        assert_eq!(session.candidates(), session.strands().find_candidates(&trie));
+        session.set_tile(Coord::new(2, 1), "e").unwrap();
+        assert_eq!(session.candidates(), before);
+    }
+
+    #[test]
+    fn test_session_invalid_tile() {
+        // a blank tile isn't a gap the words can be traced across, it's refused and the board stays as it was
+        let trie = trie();
+        let mut session = Session::new(Strands::new("CATS\nDOGY\nBEEX".to_string(), None), &trie);
+        let before = session.candidates();
+        assert_eq!(session.set_tile(Coord::new(1, 1), ""), Err(GridError::InvalidTile(String::new())));
+        assert_eq!(session.set_tile(Coord::new(1, 1), "O1"), Err(GridError::InvalidTile("O1".to_string())));
+        assert_eq!(session.strands(), &Strands::new("CATS\nDOGY\nBEEX".to_string(), None));
+        assert_eq!(session.candidates(), before);
+    }
+}
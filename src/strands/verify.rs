@@ -0,0 +1,267 @@
+// Checking a finished answer set against the board, e.g. one from another tool or an archive.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{Rules, Strands};
+use crate::model::{Coord, Path, Solution};
+use crate::trie::Trie;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Violation {
+    OutOfBounds(String, Coord), // off the grid or on a blocked cell
+    LetterMismatch(String, Coord),
+    LengthMismatch(String), // the path spells fewer or more letters than the word
+    NotAdjacent(String, Coord, Coord),
+    ReusedCell(String, Coord), // by this word, after an earlier use by it or another answer
+    Uncovered(Coord),
+    SpangramCount(Vec<String>), // the answers that span the board, when there isn't exactly one
+    NotInDictionary(String),
+    WordLength(String), // shorter or longer than the rules allow
+    Crossing(String, String), // the same word twice when a path crosses itself
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::OutOfBounds(word, coord) => write!(f, "Path for {} leaves the grid at {}.", word, coord),
+            Violation::LetterMismatch(word, coord) => write!(f, "Path for {} has the wrong letter at {}.", word, coord),
+            Violation::LengthMismatch(word) => write!(f, "Path for {} has the wrong number of cells.", word),
+            Violation::NotAdjacent(word, from, to) => write!(f, "Path for {} jumps from {} to {}.", word, from, to),
+            Violation::ReusedCell(word, coord) => write!(f, "{} uses {}, which is already taken.", word, coord),
+            Violation::Uncovered(coord) => write!(f, "{} isn't covered by any answer.", coord),
+            Violation::SpangramCount(spangrams) => write!(f, "Expected one spangram, found {:?}.", spangrams),
+            Violation::NotInDictionary(word) => write!(f, "{} isn't in the dictionary.", word),
+            Violation::WordLength(word) => write!(f, "{} is too short or too long.", word),
+            Violation::Crossing(first, second) => write!(f, "{} and {} cross.", first, second),
+        }
+    }
+}
+
+impl Strands {
+    pub fn verify(&self, solution: &Solution, trie: &Trie, rules: &Rules) -> Result<(), Vec<Violation>> {
+        // Checks every rule and reports everything that's wrong, not just the first problem.
+        // Answers are checked in alphabetical order, so the report doesn't depend on the placement order.
+        // The spangram flags on the placements aren't trusted, spanning is worked out from the paths.
+        let mut placements: Vec<_> = solution.placements.iter().collect();
+        placements.sort_by(|a, b| a.word.cmp(&b.word));
+        let mut violations = vec![];
+        let mut owners: HashMap<Coord, usize> = HashMap::new(); // index into `placements`
+        for (index, placement) in placements.iter().enumerate() {
+            let word = &placement.word;
+            self.verify_path(word, &placement.path, &mut violations);
+            if !matches!(trie.search(word), Some((_, true))) {
+                violations.push(Violation::NotInDictionary(word.clone()));
+            }
+            if !rules.allows_len(word.chars().count()) {
+                violations.push(Violation::WordLength(word.clone()));
+            }
+            for &coord in placement.path.iter() {
+                // a word going back over its own cell was already reported by `verify_path`
+                if owners.insert(coord, index).is_some_and(|owner| owner != index) {
+                    violations.push(Violation::ReusedCell(word.clone(), coord));
+                }
+            }
+        }
+
+        for coord in self.grid.coords() {
+            if !self.is_blocked(coord) && !owners.contains_key(&coord) {
+                violations.push(Violation::Uncovered(coord));
+            }
+        }
+        let spangrams: Vec<String> = placements
+            .iter()
+            .filter(|placement| self.is_spangram(&placement.path))
+            .map(|placement| placement.word.clone())
+            .collect();
+        if spangrams.len() != 1 {
+            violations.push(Violation::SpangramCount(spangrams));
+        }
+        if rules.no_crossing {
+            for (index, first) in placements.iter().enumerate() {
+                let first_steps = self.diagonal_steps(&first.path.cells());
+                for second in &placements[index..] {
+                    let second_steps = self.diagonal_steps(&second.path.cells());
+                    if first_steps.iter().any(|&(block, down_right)| second_steps.contains(&(block, !down_right))) {
+                        violations.push(Violation::Crossing(first.word.clone(), second.word.clone()));
+                    }
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    fn verify_path(&self, word: &str, path: &Path, violations: &mut Vec<Violation>) {
+        // Like `check_locked_path`, but keeps going after a problem.
+        // Letters stop being compared after the first mismatch, since the tiles after it no longer line up with the word.
+        let coords = path.coords();
+        let mut rest = Some(word);
+        for (index, &coord) in coords.iter().enumerate() {
+            if index > 0 && !self.adjacency.are_adjacent(coords[index - 1], coord, &self.grid) {
+                violations.push(Violation::NotAdjacent(word.to_string(), coords[index - 1], coord));
+            }
+            if coords[..index].contains(&coord) {
+                violations.push(Violation::ReusedCell(word.to_string(), coord));
+            }
+            if self.is_blocked(coord) {
+                violations.push(Violation::OutOfBounds(word.to_string(), coord));
+                rest = None;
+                continue;
+            }
+            let Some(letters) = rest else {
+                continue;
+            };
+            let tile = &self.grid[coord];
+            match letters.get(..tile.len()) {
+                Some(prefix) if prefix.eq_ignore_ascii_case(tile) => rest = Some(&letters[tile.len()..]),
+                _ if letters.is_empty() => {
+                    violations.push(Violation::LengthMismatch(word.to_string()));
+                    rest = None;
+                }
+                _ => {
+                    violations.push(Violation::LetterMismatch(word.to_string(), coord));
+                    rest = None;
+                }
+            }
+        }
+        if rest.is_some_and(|letters| !letters.is_empty()) {
+            violations.push(Violation::LengthMismatch(word.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Violation;
+    use crate::model::{Coord, Path, Placement, Solution};
+    use crate::strands::{Rules, Strands};
+    use crate::strands::tests::cat_dog_bee;
+    use crate::trie::Trie;
+
+    fn answers(list: &[(&str, &[(usize, usize)])]) -> Solution {
+        // spangram flags left unset, `verify` works them out itself
+        let placement = |(word, path): &(&str, &[(usize, usize)])| Placement {
+            word: word.to_string(),
+            path: Path::from(path.to_vec()),
+            spangram: false,
+        };
+        Solution::new(list.iter().map(placement).collect())
+    }
+
+    fn path(coords: &[(usize, usize)]) -> Path {
+        Path::from(coords.to_vec())
+    }
+
+    #[test]
+    fn test_verify_valid() {
+        // "CDB" runs down the left side, top to bottom
+        let (strands, mut trie) = cat_dog_bee(None, &["CDB"]);
+        let solution = answers(&[
+            ("CDB", &[(0, 0), (1, 0), (2, 0)]),
+            ("AT", &[(0, 1), (0, 2)]),
+            ("OG", &[(1, 1), (1, 2)]),
+            ("EE", &[(2, 1), (2, 2)]),
+        ]);
+        for word in ["AT", "OG", "EE"] {
+            trie.insert(word.to_string());
+        }
+        assert_eq!(strands.verify(&solution, &trie, &Rules::none()), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_spangram_count() {
+        // all three rows span left to right
+        let (strands, trie) = cat_dog_bee(None, &["CDB"]);
+        let solution = strands.solve(&trie).unwrap();
+        assert_eq!(
+            strands.verify(&solution, &trie, &Rules::none()),
+            Err(vec![Violation::SpangramCount(vec!["BEE".to_string(), "CAT".to_string(), "DOG".to_string()])])
+        );
+    }
+
+    #[test]
+    fn test_verify_reports_everything() {
+        let (strands, trie) = cat_dog_bee(None, &["CDB"]);
+        let solution = answers(&[
+            ("CAT", &[(0, 0), (0, 1), (0, 2)]),
+            ("COD", &[(0, 0), (1, 1), (1, 0)]),
+            ("BE", &[(2, 0), (2, 2)]),
+        ]);
+        assert_eq!(strands.verify(&solution, &trie, &Rules::none()), Err(vec![
+            Violation::NotAdjacent("BE".to_string(), Coord::new(2, 0), Coord::new(2, 2)),
+            Violation::NotInDictionary("BE".to_string()),
+            Violation::NotInDictionary("COD".to_string()),
+            Violation::ReusedCell("COD".to_string(), Coord::new(0, 0)),
+            Violation::Uncovered(Coord::new(1, 2)),
+            Violation::Uncovered(Coord::new(2, 1)),
+            Violation::SpangramCount(vec!["BE".to_string(), "CAT".to_string()]),
+        ]));
+    }
+
+    #[test]
+    fn test_verify_paths() {
+        let (strands, _) = cat_dog_bee(None, &["CDB"]);
+        let mut violations = vec![];
+        strands.verify_path("CAB", &path(&[(0, 0), (0, 1), (0, 3)]), &mut violations);
+        strands.verify_path("COT", &path(&[(0, 0), (0, 1), (0, 2)]), &mut violations);
+        strands.verify_path("CA", &path(&[(0, 0), (0, 1), (0, 2)]), &mut violations);
+        strands.verify_path("CATS", &path(&[(0, 0), (0, 1), (0, 2)]), &mut violations);
+        strands.verify_path("CAC", &path(&[(0, 0), (0, 1), (0, 0)]), &mut violations);
+        assert_eq!(violations, vec![
+            Violation::NotAdjacent("CAB".to_string(), Coord::new(0, 1), Coord::new(0, 3)),
+            Violation::OutOfBounds("CAB".to_string(), Coord::new(0, 3)),
+            Violation::LetterMismatch("COT".to_string(), Coord::new(0, 1)),
+            Violation::LengthMismatch("CA".to_string()),
+            Violation::LengthMismatch("CATS".to_string()),
+            Violation::ReusedCell("CAC".to_string(), Coord::new(0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn test_verify_reused_once() {
+        // a path back over its own cell is one problem, not one per check
+        let (strands, trie) = cat_dog_bee(None, &["CDB"]);
+        let solution = answers(&[("CAC", &[(0, 0), (0, 1), (0, 0)])]);
+        let errors = strands.verify(&solution, &trie, &Rules::none()).unwrap_err();
+        let reused: Vec<_> = errors.iter().filter(|violation| matches!(violation, Violation::ReusedCell(..))).collect();
+        assert_eq!(reused, vec![&Violation::ReusedCell("CAC".to_string(), Coord::new(0, 0))]);
+    }
+
+    #[test]
+    fn test_verify_word_length() {
+        let (strands, mut trie) = cat_dog_bee(None, &["CDB"]);
+        trie.insert("CDB".to_string());
+        let solution = strands.solve(&trie).unwrap();
+        let rules = Rules { min_word_len: 4, ..Rules::none() };
+        let errors = strands.verify(&solution, &trie, &rules).unwrap_err();
+        assert_eq!(errors.iter().filter(|violation| matches!(violation, Violation::WordLength(_))).count(), 3);
+    }
+
+    #[test]
+    fn test_verify_crossing() {
+        //ABC
+        //BAC
+        //DDD
+        let strands = Strands::new("ABC\nBAC\nDDD".to_string(), None);
+        let mut trie = Trie::new();
+        for word in ["AA", "BB", "CC", "DDD"] {
+            trie.insert(word.to_string());
+        }
+        let solution = answers(&[
+            ("AA", &[(0, 0), (1, 1)]),
+            ("BB", &[(0, 1), (1, 0)]),
+            ("CC", &[(0, 2), (1, 2)]),
+            ("DDD", &[(2, 0), (2, 1), (2, 2)]),
+        ]);
+        assert_eq!(strands.verify(&solution, &trie, &Rules::none()), Ok(()));
+        assert_eq!(
+            strands.verify(&solution, &trie, &Rules { no_crossing: true, ..Rules::none() }),
+            Err(vec![Violation::Crossing("AA".to_string(), "BB".to_string())])
+        );
+    }
+}
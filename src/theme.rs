@@ -0,0 +1,123 @@
+// Word associations used to tell which tiling fits the puzzle's theme clue.
+// Loaded from a local text file that can mix two kinds of lines:
+//   category lists:     `fruit: apple, banana, cherry`
+//   co-occurrence pairs: `apple pie 0.8`
+// Blank lines and lines starting with `#` are ignored. Everything is case agnostic.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Associations {
+    categories: HashMap<String, HashSet<String>>,
+    pairs: HashMap<(String, String), f64>, // stored with the words in sorted order
+}
+
+impl Associations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut associations = Self::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, words)) = line.split_once(':') {
+                let words = words.split(',').map(str::trim).filter(|word| !word.is_empty());
+                associations.add_category(name.trim(), words);
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if let [first, second, score] = parts[..] {
+                if let Ok(score) = score.parse() {
+                    associations.add_pair(first, second, score);
+                }
+            }
+        }
+        associations
+    }
+
+    pub fn add_category<'a>(&mut self, name: &str, words: impl IntoIterator<Item = &'a str>) {
+        self.categories
+            .entry(name.to_ascii_lowercase())
+            .or_default()
+            .extend(words.into_iter().map(|word| word.to_ascii_lowercase()));
+    }
+
+    pub fn add_pair(&mut self, first: &str, second: &str, score: f64) {
+        self.pairs.insert(Self::pair_key(first, second), score);
+    }
+
+    pub fn relatedness(&self, word: &str, clue: &str) -> f64 {
+        // How strongly `word` relates to the clue, summed over the words of the clue.
+        // Each clue word adds its co-occurrence score with `word`, and 1 for every category that
+        // is named after it or lists it, and that also lists `word`.
+        let word = word.to_ascii_lowercase();
+        let mut score = 0.0;
+        for clue_word in Self::clue_words(clue) {
+            score += self.pairs.get(&Self::pair_key(&word, &clue_word)).unwrap_or(&0.0);
+            for (name, members) in &self.categories {
+                if members.contains(&word) && (*name == clue_word || members.contains(&clue_word)) {
+                    score += 1.0;
+                }
+            }
+        }
+        score
+    }
+
+    fn clue_words(clue: &str) -> Vec<String> {
+        clue.split(|c: char| !c.is_alphabetic())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_ascii_lowercase())
+            .collect()
+    }
+
+    fn pair_key(first: &str, second: &str) -> (String, String) {
+        let (first, second) = (first.to_ascii_lowercase(), second.to_ascii_lowercase());
+        if first <= second {
+            (first, second)
+        } else {
+            (second, first)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Associations;
+
+    #[test]
+    fn test_parse() {
+        let associations = Associations::parse("# pets\npets: Cat, dog\n\ncat mouse 0.5\nnot a pair\n");
+        let mut good = Associations::new();
+        good.add_category("pets", ["cat", "dog"]);
+        good.add_pair("mouse", "cat", 0.5);
+        assert_eq!(associations, good);
+    }
+
+    #[test]
+    fn test_relatedness_category() {
+        let associations = Associations::parse("pets: cat, dog, fish\nbirds: robin, wren");
+        // named after the clue word
+        assert_eq!(associations.relatedness("DOG", "Furry pets"), 1.0);
+        // listed alongside the clue word
+        assert_eq!(associations.relatedness("dog", "Cat people"), 1.0);
+        assert_eq!(associations.relatedness("wren", "Furry pets"), 0.0);
+    }
+
+    #[test]
+    fn test_relatedness_pairs() {
+        let associations = Associations::parse("cheese mouse 0.75\ncheese cracker 0.5");
+        assert_eq!(associations.relatedness("mouse", "Say cheese!"), 0.75);
+        assert_eq!(associations.relatedness("cracker", "cheese"), 0.5);
+        assert_eq!(associations.relatedness("cheese", "mouse cracker"), 1.25);
+    }
+}
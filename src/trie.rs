@@ -1,4 +1,5 @@
 // Quick and dirty trie implementation
+#![allow(clippy::vec_box, clippy::manual_ignore_case_cmp)]
 
 #[derive(Debug, PartialEq)]
 pub struct Trie{
@@ -70,6 +71,12 @@ impl Trie {
 
 }
 
+impl Default for Trie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Node{
     fn new(letval: Option<char>) -> Self {
         Node {